@@ -0,0 +1,128 @@
+use std::{collections::BTreeMap, fmt, path::Path, str::FromStr};
+
+use anyhow::{anyhow, bail, Context};
+
+use crate::solution::Part;
+
+pub const ANSWERS_PATH: &str = "answers.txt";
+
+/// Accepted answers keyed by day and part, stored one per line as `<day>.<part> <answer>`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct AnswerStore {
+    answers: BTreeMap<(usize, Part), String>,
+}
+
+impl AnswerStore {
+    /// Loads the store from `path`, treating a missing file as an empty store.
+    pub fn load(path: impl AsRef<Path>) -> anyhow::Result<AnswerStore> {
+        let path = path.as_ref();
+        if !path.exists() {
+            return Ok(AnswerStore::default());
+        }
+        std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read {}", path.display()))?
+            .parse()
+            .with_context(|| format!("Failed to parse {}", path.display()))
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> anyhow::Result<()> {
+        let path = path.as_ref();
+        std::fs::write(path, self.to_string())
+            .with_context(|| format!("Failed to write {}", path.display()))
+    }
+
+    pub fn get(&self, day: usize, part: Part) -> Option<&str> {
+        self.answers.get(&(day, part)).map(String::as_str)
+    }
+
+    pub fn set(&mut self, day: usize, part: Part, answer: impl Into<String>) {
+        self.answers.insert((day, part), answer.into());
+    }
+
+    /// Compares a freshly computed answer against the stored one.
+    pub fn check(&self, day: usize, part: Part, actual: &str) -> Check {
+        match self.get(day, part) {
+            Some(expected) if expected == actual => Check::Pass,
+            Some(expected) => Check::Changed {
+                expected: expected.to_string(),
+            },
+            None => Check::Missing,
+        }
+    }
+}
+
+impl FromStr for AnswerStore {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut store = AnswerStore::default();
+        for (i, line) in s.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let context = || format!("line {}: {line:?}", i + 1);
+            let (key, answer) = line
+                .split_once(' ')
+                .ok_or(anyhow!("Missing answer"))
+                .with_context(context)?;
+            let (day, part) = key
+                .split_once('.')
+                .ok_or(anyhow!("Expected `<day>.<part>`"))
+                .with_context(context)?;
+            let day: usize = day.parse().with_context(context)?;
+            let part = match part {
+                "1" => Part::One,
+                "2" => Part::Two,
+                _ => bail!("{}: Invalid part `{part}`", context()),
+            };
+            store.set(day, part, answer.trim());
+        }
+        Ok(store)
+    }
+}
+
+impl fmt::Display for AnswerStore {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for ((day, part), answer) in &self.answers {
+            writeln!(f, "{day}.{part} {answer}")?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Check {
+    Pass,
+    Changed { expected: String },
+    Missing,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_round_trip() {
+        let mut store = AnswerStore::default();
+        store.set(3, Part::Two, "84907174");
+        store.set(1, Part::One, "54159");
+        let text = store.to_string();
+        assert_eq!(text, "1.1 54159\n3.2 84907174\n");
+        assert_eq!(AnswerStore::from_str(&text).unwrap(), store);
+    }
+
+    #[test]
+    fn test_check() {
+        let store = AnswerStore::from_str("# accepted\n4.1 13\n").unwrap();
+        assert_eq!(store.check(4, Part::One, "13"), Check::Pass);
+        assert_eq!(
+            store.check(4, Part::One, "14"),
+            Check::Changed {
+                expected: "13".to_string()
+            }
+        );
+        assert_eq!(store.check(4, Part::Two, "30"), Check::Missing);
+        assert!(AnswerStore::from_str("4.3 13").is_err());
+    }
+}
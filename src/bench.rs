@@ -0,0 +1,92 @@
+use std::time::{Duration, Instant};
+
+use crate::solution::Part;
+
+/// Summary of repeated timings of a single stage.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Stats {
+    pub min: Duration,
+    pub median: Duration,
+    pub mean: Duration,
+}
+
+impl Stats {
+    pub fn from_samples(mut samples: Vec<Duration>) -> Stats {
+        assert!(!samples.is_empty(), "no samples to summarise");
+        samples.sort();
+        let mid = samples.len() / 2;
+        let median = if samples.len().is_multiple_of(2) {
+            (samples[mid - 1] + samples[mid]) / 2
+        } else {
+            samples[mid]
+        };
+        let total: Duration = samples.iter().sum();
+        Stats {
+            min: samples[0],
+            median,
+            mean: total / samples.len() as u32,
+        }
+    }
+}
+
+/// Runs `f` `iterations` times and summarises the timings, stopping at the first error.
+pub fn measure<T>(
+    iterations: usize,
+    mut f: impl FnMut() -> anyhow::Result<T>,
+) -> anyhow::Result<Stats> {
+    let mut samples = Vec::with_capacity(iterations);
+    for _ in 0..iterations.max(1) {
+        let start = Instant::now();
+        std::hint::black_box(f()?);
+        samples.push(start.elapsed());
+    }
+    Ok(Stats::from_samples(samples))
+}
+
+#[derive(Debug, Clone)]
+pub struct BenchReport {
+    pub day: usize,
+    pub parse: Stats,
+    pub parts: Vec<(Part, Stats)>,
+}
+
+impl BenchReport {
+    pub fn part(&self, part: Part) -> Option<Stats> {
+        self.parts.iter().find(|(p, _)| *p == part).map(|(_, s)| *s)
+    }
+
+    /// Median parse time plus the median of every benchmarked part.
+    pub fn total(&self) -> Duration {
+        self.parse.median + self.parts.iter().map(|(_, s)| s.median).sum::<Duration>()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_stats() {
+        let ms = Duration::from_millis;
+        let stats = Stats::from_samples(vec![ms(4), ms(1), ms(3), ms(2)]);
+        assert_eq!(stats.min, ms(1));
+        assert_eq!(stats.median, Duration::from_micros(2500));
+        assert_eq!(stats.mean, Duration::from_micros(2500));
+
+        let stats = Stats::from_samples(vec![ms(9), ms(1), ms(2)]);
+        assert_eq!(stats.median, ms(2));
+        assert_eq!(stats.mean, ms(4));
+    }
+
+    #[test]
+    fn test_measure_stops_on_error() {
+        let mut calls = 0;
+        let result = measure(5, || {
+            calls += 1;
+            anyhow::ensure!(calls < 3, "boom");
+            Ok(())
+        });
+        assert!(result.is_err());
+        assert_eq!(calls, 3);
+    }
+}
@@ -0,0 +1,500 @@
+use std::{path::PathBuf, str::FromStr};
+
+use anyhow::{anyhow, bail, Context};
+
+use crate::{days::Handful, solution::Part, term::ColorMode};
+
+pub const USAGE: &str = "\
+Usage: aoc2023 <command> [options] [selector...]
+
+Commands:
+  run [selector...]     Solve the selected days against the puzzle input (default: all)
+      --save            Record the answers in answers.txt as the accepted answers
+      --explain         Print a trace of how each answer is reached (days that support it)
+      --bag=CUBES       Day 2's bag of cubes, e.g. `--bag=\"12 red, 13 green, 14 blue, 3 teal\"`
+                        (not with --save)
+  check [selector...]   Solve the selected days and compare against answers.txt
+  test [selector...]    Solve the selected days against the examples and compare answers
+  bench [selector...]   Time parsing and each part against the puzzle input
+      --iterations=N    Number of timed runs per stage (default: 10)
+  render                Draw the day 3 schematic, colouring part numbers, symbols and gears
+      --example         Use the example instead of the puzzle input
+      --crop=X0,Y0,X1,Y1
+                        Only draw the cells between two corners (inclusive)
+      --color=WHEN      auto, always or never (default: auto, colour only on a terminal)
+      --image=PATH      Write a .png or .ppm image instead of drawing to the terminal
+      --scale=N         Pixels per cell in the image (default: 4)
+  calibrate [PATH]      Run the day 1 calibration over PATH, or stdin if omitted or `-`
+      --vocab=NAME|FILE Number words to recognise: a built-in vocabulary (digits, english,
+                        english-full, french, german, spanish) or a file of `<words> <value>`
+                        lines (default: english)
+      --explain         Print the tokens and value found on each line
+  list                  List the implemented days
+  help                  Show this message
+
+Selectors:
+  all                   Every implemented day
+  3                     Both parts of day 3
+  3.2                   Only part 2 of day 3
+  1-5                   Days 1 through 5
+
+A bare selector such as `aoc2023 3` is shorthand for `aoc2023 run 3`.";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Command {
+    Run {
+        selectors: Vec<Selector>,
+        save: bool,
+        explain: bool,
+        bag: Option<Handful>,
+    },
+    Check(Vec<Selector>),
+    Test(Vec<Selector>),
+    Bench {
+        selectors: Vec<Selector>,
+        iterations: usize,
+    },
+    Render {
+        example: bool,
+        crop: Option<((usize, usize), (usize, usize))>,
+        color: ColorMode,
+        image: Option<PathBuf>,
+        scale: usize,
+    },
+    Calibrate {
+        path: Option<PathBuf>,
+        vocab: String,
+        explain: bool,
+    },
+    List,
+    Help,
+}
+
+impl Command {
+    pub fn parse<I, S>(args: I) -> anyhow::Result<Command>
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        let args: Vec<String> = args.into_iter().map(|a| a.as_ref().to_string()).collect();
+        let Some((command, rest)) = args.split_first() else {
+            return Ok(Command::Help);
+        };
+
+        let selectors = |rest: &[String]| -> anyhow::Result<Vec<Selector>> {
+            if rest.is_empty() {
+                return Ok(vec![Selector::All]);
+            }
+            rest.iter().map(|s| Selector::from_str(s)).collect()
+        };
+
+        let command = match command.as_str() {
+            "run" => {
+                let mut flags = Flags::new(rest);
+                let save = flags.take("save");
+                let explain = flags.take("explain");
+                let bag = flags
+                    .value("bag")
+                    .map(|b| b.parse().with_context(|| format!("Invalid --bag `{b}`")))
+                    .transpose()?;
+                if save && bag.is_some() {
+                    // answers.txt records the puzzle's answers, which `check` runs without a bag.
+                    bail!("--save can't be combined with --bag");
+                }
+                Command::Run {
+                    selectors: selectors(&flags.finish()?)?,
+                    save,
+                    explain,
+                    bag,
+                }
+            }
+            "check" => Command::Check(selectors(&Flags::new(rest).finish()?)?),
+            "test" => Command::Test(selectors(&Flags::new(rest).finish()?)?),
+            "bench" => {
+                let mut flags = Flags::new(rest);
+                let iterations = match flags.value("iterations") {
+                    Some(n) => n.parse().ok().filter(|n| *n > 0).ok_or_else(|| {
+                        anyhow!("Invalid --iterations `{n}`: expected a positive number")
+                    })?,
+                    None => 10,
+                };
+                Command::Bench {
+                    selectors: selectors(&flags.finish()?)?,
+                    iterations,
+                }
+            }
+            "render" => {
+                let mut flags = Flags::new(rest);
+                let example = flags.take("example");
+                let crop = flags.value("crop").map(|c| parse_crop(&c)).transpose()?;
+                let color = match flags.value("color") {
+                    Some(c) => c.parse()?,
+                    None => ColorMode::Auto,
+                };
+                let image = flags.value("image").map(PathBuf::from);
+                let scale = match flags.value("scale") {
+                    Some(n) => n.parse().ok().filter(|n| *n > 0).ok_or_else(|| {
+                        anyhow!("Invalid --scale `{n}`: expected a positive number")
+                    })?,
+                    None => 4,
+                };
+                let rest = flags.finish()?;
+                if let Some(arg) = rest.first() {
+                    bail!("Unexpected argument `{arg}` to render");
+                }
+                Command::Render {
+                    example,
+                    crop,
+                    color,
+                    image,
+                    scale,
+                }
+            }
+            "calibrate" => {
+                let mut flags = Flags::new(rest);
+                let vocab = flags
+                    .value("vocab")
+                    .unwrap_or_else(|| "english".to_string());
+                let explain = flags.take("explain");
+                let path = match flags.finish()?.as_slice() {
+                    [] => None,
+                    [path] if path == "-" => None,
+                    [path] => Some(PathBuf::from(path)),
+                    [_, extra, ..] => bail!("Unexpected argument `{extra}` to calibrate"),
+                };
+                Command::Calibrate {
+                    path,
+                    vocab,
+                    explain,
+                }
+            }
+            "list" => Command::List,
+            "help" | "-h" | "--help" => Command::Help,
+            c if c == "all" || c.starts_with(|ch: char| ch.is_ascii_digit()) => {
+                Command::parse(["run"].into_iter().chain(args.iter().map(String::as_str)))?
+            }
+            _ => bail!("Unknown command `{command}`; run `aoc2023 help` for usage"),
+        };
+        Ok(command)
+    }
+}
+
+/// `--name` and `--name=value` options mixed in with a command's positional arguments.
+struct Flags {
+    flags: Vec<(String, Option<String>)>,
+    positional: Vec<String>,
+}
+
+impl Flags {
+    fn new(args: &[String]) -> Flags {
+        let mut flags = Vec::new();
+        let mut positional = Vec::new();
+        for arg in args {
+            match arg.strip_prefix("--") {
+                Some(flag) => match flag.split_once('=') {
+                    Some((name, value)) => flags.push((name.to_string(), Some(value.to_string()))),
+                    None => flags.push((flag.to_string(), None)),
+                },
+                None => positional.push(arg.clone()),
+            }
+        }
+        Flags { flags, positional }
+    }
+
+    /// Removes a boolean flag, returning whether it was present.
+    fn take(&mut self, name: &str) -> bool {
+        let before = self.flags.len();
+        self.flags.retain(|(n, v)| !(n == name && v.is_none()));
+        self.flags.len() != before
+    }
+
+    /// Removes a `--name=value` flag, returning its value if present.
+    fn value(&mut self, name: &str) -> Option<String> {
+        let i = self
+            .flags
+            .iter()
+            .position(|(n, v)| n == name && v.is_some())?;
+        self.flags.remove(i).1
+    }
+
+    /// Returns the positional arguments, rejecting any flag that was not taken.
+    fn finish(self) -> anyhow::Result<Vec<String>> {
+        if let Some((name, _)) = self.flags.first() {
+            bail!("Unknown option `--{name}`; run `aoc2023 help` for usage");
+        }
+        Ok(self.positional)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Selector {
+    All,
+    Range(usize, usize),
+    Day(usize, Option<Part>),
+}
+
+impl FromStr for Selector {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s == "all" {
+            return Ok(Selector::All);
+        }
+        if let Some((start, end)) = s.split_once('-') {
+            let start = parse_day(start)?;
+            let end = parse_day(end)?;
+            if start > end {
+                bail!("Invalid range `{s}`: {start} is after {end}");
+            }
+            return Ok(Selector::Range(start, end));
+        }
+        if let Some((day, part)) = s.split_once('.') {
+            let part = match part {
+                "1" => Part::One,
+                "2" => Part::Two,
+                _ => bail!("Invalid part `{part}` in `{s}`: expected 1 or 2"),
+            };
+            return Ok(Selector::Day(parse_day(day)?, Some(part)));
+        }
+        Ok(Selector::Day(parse_day(s)?, None))
+    }
+}
+
+/// Parses `x0,y0,x1,y1` into two corners.
+fn parse_crop(s: &str) -> anyhow::Result<((usize, usize), (usize, usize))> {
+    let coords: Vec<usize> = s
+        .split(',')
+        .map(|c| c.trim().parse())
+        .collect::<Result<_, _>>()
+        .map_err(|_| anyhow!("Invalid --crop `{s}`: expected x0,y0,x1,y1"))?;
+    let [x0, y0, x1, y1] = coords[..] else {
+        bail!("Invalid --crop `{s}`: expected x0,y0,x1,y1");
+    };
+    Ok(((x0.min(x1), y0.min(y1)), (x0.max(x1), y0.max(y1))))
+}
+
+fn parse_day(s: &str) -> anyhow::Result<usize> {
+    let day: usize = s
+        .parse()
+        .map_err(|_| anyhow!("Invalid day `{s}`: expected a number, range or `all`"))?;
+    if !(1..=25).contains(&day) {
+        bail!("Invalid day {day}: days run from 1 to 25");
+    }
+    Ok(day)
+}
+
+/// A day to execute along with the parts requested for it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Target {
+    pub day: usize,
+    pub parts: Vec<Part>,
+}
+
+/// Expands selectors into targets, rejecting any day that is not in `implemented`.
+pub fn resolve(selectors: &[Selector], implemented: &[usize]) -> anyhow::Result<Vec<Target>> {
+    let mut targets: Vec<Target> = Vec::new();
+    let mut add = |day: usize, parts: &[Part]| match targets.iter_mut().find(|t| t.day == day) {
+        Some(target) => {
+            for part in parts {
+                if !target.parts.contains(part) {
+                    target.parts.push(*part);
+                }
+            }
+            target.parts.sort();
+        }
+        None => targets.push(Target {
+            day,
+            parts: parts.to_vec(),
+        }),
+    };
+
+    for selector in selectors {
+        match *selector {
+            Selector::All => implemented.iter().for_each(|d| add(*d, &Part::ALL)),
+            Selector::Range(start, end) => {
+                let missing: Vec<String> = (start..=end)
+                    .filter(|d| !implemented.contains(d))
+                    .map(|d| d.to_string())
+                    .collect();
+                if !missing.is_empty() {
+                    bail!(
+                        "Day(s) {} in range {start}-{end} not implemented (available: {})",
+                        missing.join(", "),
+                        describe(implemented)
+                    );
+                }
+                (start..=end).for_each(|d| add(d, &Part::ALL));
+            }
+            Selector::Day(day, part) => {
+                if !implemented.contains(&day) {
+                    bail!(
+                        "Day {day} is not implemented (available: {})",
+                        describe(implemented)
+                    );
+                }
+                match part {
+                    Some(part) => add(day, &[part]),
+                    None => add(day, &Part::ALL),
+                }
+            }
+        }
+    }
+    Ok(targets)
+}
+
+fn describe(implemented: &[usize]) -> String {
+    match (implemented.first(), implemented.last()) {
+        (Some(first), Some(last)) if last - first + 1 == implemented.len() => {
+            format!("{first}-{last}")
+        }
+        _ => implemented
+            .iter()
+            .map(|d| d.to_string())
+            .collect::<Vec<_>>()
+            .join(", "),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::days::Color;
+
+    use super::*;
+
+    #[test]
+    fn test_parse_selectors() {
+        assert_eq!(Selector::from_str("all").unwrap(), Selector::All);
+        assert_eq!(Selector::from_str("1-5").unwrap(), Selector::Range(1, 5));
+        assert_eq!(
+            Selector::from_str("3.2").unwrap(),
+            Selector::Day(3, Some(Part::Two))
+        );
+        assert_eq!(Selector::from_str("7").unwrap(), Selector::Day(7, None));
+        assert!(Selector::from_str("0").is_err());
+        assert!(Selector::from_str("5-1").is_err());
+        assert!(Selector::from_str("3.3").is_err());
+        assert!(Selector::from_str("x").is_err());
+    }
+
+    #[test]
+    fn test_parse_command() {
+        assert_eq!(
+            Command::parse(["3"]).unwrap(),
+            Command::Run {
+                selectors: vec![Selector::Day(3, None)],
+                save: false,
+                explain: false,
+                bag: None
+            }
+        );
+        assert_eq!(
+            Command::parse(["run", "--save", "1-2", "--explain"]).unwrap(),
+            Command::Run {
+                selectors: vec![Selector::Range(1, 2)],
+                save: true,
+                explain: true,
+                bag: None
+            }
+        );
+        let Command::Run { bag, .. } = Command::parse(["run", "2", "--bag=1 red, 2 teal"]).unwrap()
+        else {
+            panic!("expected a run command");
+        };
+        assert_eq!(bag.unwrap().get(Color::new("teal")), 2);
+        assert!(Command::parse(["run", "--bag=lots of red"]).is_err());
+        assert!(Command::parse(["run", "2", "--bag=1 red", "--save"]).is_err());
+        assert_eq!(
+            Command::parse(["3", "--save"]).unwrap(),
+            Command::Run {
+                selectors: vec![Selector::Day(3, None)],
+                save: true,
+                explain: false,
+                bag: None
+            }
+        );
+        assert!(Command::parse(["3", "--iterations=2"]).is_err());
+        assert!(Command::parse(["check", "--save"]).is_err());
+        assert_eq!(
+            Command::parse(["bench"]).unwrap(),
+            Command::Bench {
+                selectors: vec![Selector::All],
+                iterations: 10
+            }
+        );
+        assert_eq!(
+            Command::parse(["bench", "4", "--iterations=3"]).unwrap(),
+            Command::Bench {
+                selectors: vec![Selector::Day(4, None)],
+                iterations: 3
+            }
+        );
+        assert!(Command::parse(["bench", "--iterations=0"]).is_err());
+        assert_eq!(
+            Command::parse(["render", "--example", "--crop=5,0,0,3", "--color=never"]).unwrap(),
+            Command::Render {
+                example: true,
+                crop: Some(((0, 0), (5, 3))),
+                color: ColorMode::Never,
+                image: None,
+                scale: 4
+            }
+        );
+        assert_eq!(
+            Command::parse(["render", "--image=out.png", "--scale=2"]).unwrap(),
+            Command::Render {
+                example: false,
+                crop: None,
+                color: ColorMode::Auto,
+                image: Some(PathBuf::from("out.png")),
+                scale: 2
+            }
+        );
+        assert!(Command::parse(["render", "--scale=0"]).is_err());
+        assert_eq!(
+            Command::parse(["calibrate", "--vocab=french", "in.txt"]).unwrap(),
+            Command::Calibrate {
+                path: Some(PathBuf::from("in.txt")),
+                vocab: "french".to_string(),
+                explain: false
+            }
+        );
+        assert_eq!(
+            Command::parse(["calibrate", "-", "--explain"]).unwrap(),
+            Command::Calibrate {
+                path: None,
+                vocab: "english".to_string(),
+                explain: true
+            }
+        );
+        assert!(Command::parse(["calibrate", "a", "b"]).is_err());
+        assert!(Command::parse(["render", "--crop=1,2"]).is_err());
+        assert!(Command::parse(["render", "--color=sometimes"]).is_err());
+        assert_eq!(Command::parse(Vec::<String>::new()).unwrap(), Command::Help);
+        assert!(Command::parse(["frobnicate"]).is_err());
+    }
+
+    #[test]
+    fn test_resolve() {
+        let implemented = [1, 2, 3, 4, 5];
+        let targets = resolve(
+            &[Selector::Day(3, Some(Part::Two)), Selector::Range(2, 3)],
+            &implemented,
+        )
+        .unwrap();
+        assert_eq!(
+            targets,
+            vec![
+                Target {
+                    day: 3,
+                    parts: vec![Part::One, Part::Two]
+                },
+                Target {
+                    day: 2,
+                    parts: vec![Part::One, Part::Two]
+                },
+            ]
+        );
+        assert!(resolve(&[Selector::Day(9, None)], &implemented).is_err());
+        assert!(resolve(&[Selector::Range(4, 6)], &implemented).is_err());
+    }
+}
@@ -1,73 +1,512 @@
-use crate::util::read_input;
-
-pub fn day01() {
-    problem1();
-    problem2();
-}
-
-fn problem1() {
-    let input = read_input(1, 1);
-    let mut numbers: Vec<u64> = Vec::new();
-    for line in input.lines() {
-        let mut chars = line.chars().filter(|c| c.is_digit(10));
-        let first = chars.next().unwrap();
-        let n1 = first.to_digit(10).unwrap() as u64 * 10;
-        let n2 = chars.last().unwrap_or(first).to_digit(10).unwrap() as u64;
-        numbers.push(n1 + n2);
-    }
-    println!("{}", numbers.iter().sum::<u64>());
-}
-
-fn problem2() {
-    let number_words = [
-        ("one", 1),
-        ("1", 1),
-        ("two", 2),
-        ("2", 2),
-        ("three", 3),
-        ("3", 3),
-        ("four", 4),
-        ("4", 4),
-        ("five", 5),
-        ("5", 5),
-        ("six", 6),
-        ("6", 6),
-        ("seven", 7),
-        ("7", 7),
-        ("eight", 8),
-        ("8", 8),
-        ("nine", 9),
-        ("9", 9),
-    ];
-
-    let input = read_input(1, 2);
-    let mut numbers: Vec<u64> = Vec::new();
-    for line in input.lines() {
-        let mut l = line;
-        let mut first = None;
-        let mut last = None;
-        while l.len() > 0 {
-            let mut found = false;
-            for (word, number) in number_words.iter() {
-                if let Some(rest) = l.strip_prefix(word) {
-                    if first.is_none() {
-                        first = Some(*number);
-                        l = rest;
-                        found = true;
-                        break;
-                    } else {
-                        last = Some(*number);
+use std::{
+    cmp::Reverse,
+    fmt::{self, Display},
+    io::BufRead,
+    path::Path,
+    str::FromStr,
+};
+
+use anyhow::{anyhow, bail, Context};
+
+use crate::{
+    solution::{Part, Solution},
+    util::{Matcher, State},
+};
+
+pub struct Day01;
+
+impl Solution for Day01 {
+    const DAY: usize = 1;
+
+    type Input = Vec<String>;
+    type Answer1 = u64;
+    type Answer2 = u64;
+
+    fn parse(&self, input: &str) -> anyhow::Result<Self::Input> {
+        Ok(input.lines().map(String::from).collect())
+    }
+
+    fn part1(&self, input: &Self::Input) -> anyhow::Result<u64> {
+        problem1(input)
+    }
+
+    fn part2(&self, input: &Self::Input) -> anyhow::Result<u64> {
+        problem2(input)
+    }
+
+    fn explain(&self, input: &Self::Input, part: Part) -> anyhow::Result<Option<String>> {
+        Ok(Some(tokens(part)?.explain(input)))
+    }
+}
+
+const DIGITS: [(&str, u64); 9] = [
+    ("1", 1),
+    ("2", 2),
+    ("3", 3),
+    ("4", 4),
+    ("5", 5),
+    ("6", 6),
+    ("7", 7),
+    ("8", 8),
+    ("9", 9),
+];
+
+/// Built-in vocabularies in the [`Vocabulary`] file format. `english` is the puzzle's own.
+const BUILTIN_VOCABULARIES: [(&str, &str); 6] = [
+    ("digits", ""),
+    (
+        "english",
+        "one 1\ntwo 2\nthree 3\nfour 4\nfive 5\nsix 6\nseven 7\neight 8\nnine 9",
+    ),
+    (
+        "english-full",
+        "zero 0\none 1\ntwo 2\nthree 3\nfour 4\nfive 5\nsix 6\nseven 7\neight 8\nnine 9\n\
+         ten 10\neleven 11\ntwelve 12\nthirteen 13\nfourteen 14\nfifteen 15\nsixteen 16\n\
+         seventeen 17\neighteen 18\nnineteen 19\ntwenty 20\nthirty 30\nforty 40\nfifty 50\n\
+         sixty 60\nseventy 70\neighty 80\nninety 90",
+    ),
+    (
+        "french",
+        "un 1\ndeux 2\ntrois 3\nquatre 4\ncinq 5\nsix 6\nsept 7\nhuit 8\nneuf 9",
+    ),
+    (
+        "german",
+        "eins 1\nzwei 2\ndrei 3\nvier 4\nfünf 5\nsechs 6\nsieben 7\nacht 8\nneun 9",
+    ),
+    (
+        "spanish",
+        "uno 1\ndos 2\ntres 3\ncuatro 4\ncinco 5\nseis 6\nsiete 7\nocho 8\nnueve 9",
+    ),
+];
+
+/// Number words and the values they stand for. Digits are always recognised on top of these.
+///
+/// The file format is one `<words> <value>` entry per line, where everything before the last
+/// space is the text to match, so `twenty one 21` is allowed. Blank lines and `#` comments are
+/// skipped.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Vocabulary {
+    words: Vec<(String, u64)>,
+}
+
+impl Vocabulary {
+    pub fn builtin_names() -> impl Iterator<Item = &'static str> {
+        BUILTIN_VOCABULARIES.iter().map(|(name, _)| *name)
+    }
+
+    pub fn builtin(name: &str) -> Option<Vocabulary> {
+        let (_, words) = BUILTIN_VOCABULARIES.iter().find(|(n, _)| *n == name)?;
+        Some(words.parse().expect("built-in vocabularies are valid"))
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> anyhow::Result<Vocabulary> {
+        let path = path.as_ref();
+        std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read {}", path.display()))?
+            .parse()
+            .with_context(|| format!("Failed to parse {}", path.display()))
+    }
+
+    /// A built-in vocabulary by name, or else one loaded from the file at `name`.
+    pub fn resolve(name: &str) -> anyhow::Result<Vocabulary> {
+        match Vocabulary::builtin(name) {
+            Some(vocabulary) => Ok(vocabulary),
+            None if Path::new(name).exists() => Vocabulary::load(name),
+            None => bail!(
+                "Unknown vocabulary `{name}`: expected a file or one of {}",
+                Vocabulary::builtin_names().collect::<Vec<_>>().join(", ")
+            ),
+        }
+    }
+
+    pub fn words(&self) -> &[(String, u64)] {
+        &self.words
+    }
+
+    /// A matcher for the digits `1`-`9` and every word in the vocabulary.
+    pub fn matcher(&self) -> anyhow::Result<Matcher<u64>> {
+        let digits = DIGITS.map(|(d, v)| (d.as_bytes(), v));
+        let words = self.words.iter().map(|(w, v)| (w.as_bytes(), *v));
+        Matcher::new(digits.into_iter().chain(words))
+    }
+
+    /// A calibrator recognising the same digits and words as [`Vocabulary::matcher`].
+    pub fn calibrator(&self) -> anyhow::Result<Calibrator> {
+        Ok(Calibrator {
+            matcher: self.matcher()?,
+        })
+    }
+}
+
+impl FromStr for Vocabulary {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut words = Vec::new();
+        for (i, line) in s.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let context = || format!("line {}: {line:?}", i + 1);
+            let (word, value) = line
+                .rsplit_once(char::is_whitespace)
+                .ok_or(anyhow!("Expected `<words> <value>`"))
+                .with_context(context)?;
+            let value = value.parse().with_context(context)?;
+            words.push((word.trim_end().to_string(), value));
+        }
+        Ok(Vocabulary { words })
+    }
+}
+
+/// Part 1 only recognises digits; part 2 also spells them out.
+fn tokens(part: Part) -> anyhow::Result<Calibrator> {
+    let name = match part {
+        Part::One => "digits",
+        Part::Two => "english",
+    };
+    Vocabulary::builtin(name)
+        .expect("puzzle vocabularies are built in")
+        .calibrator()
+}
+
+fn problem1(input: &[String]) -> anyhow::Result<u64> {
+    tokens(Part::One)?.calibrate(input)
+}
+
+fn problem2(input: &[String]) -> anyhow::Result<u64> {
+    tokens(Part::Two)?.calibrate(input)
+}
+
+/// A digit or number word found in a line.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CalibrationToken<'a> {
+    pub text: &'a str,
+    /// Byte offset of the token within its line.
+    pub offset: usize,
+    pub value: u64,
+}
+
+impl CalibrationToken<'_> {
+    pub fn is_word(&self) -> bool {
+        !self.text.bytes().all(|b| b.is_ascii_digit())
+    }
+}
+
+impl Display for CalibrationToken<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let kind = if self.is_word() { "word" } else { "digit" };
+        write!(f, "{kind} {:?} at {}", self.text, self.offset)
+    }
+}
+
+/// The tokens that make up one line's calibration value.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LineCalibration<'a> {
+    pub first: CalibrationToken<'a>,
+    pub last: CalibrationToken<'a>,
+    pub value: u64,
+}
+
+fn leading_digit(mut n: u64) -> u64 {
+    while n >= 10 {
+        n /= 10;
+    }
+    n
+}
+
+/// How many offending lines an error names before summarising the rest.
+const SHOWN: usize = 10;
+
+fn no_digit_error(count: u64, shown: &[String]) -> anyhow::Error {
+    let mut message = shown.join(", ");
+    if count > shown.len() as u64 {
+        message += &format!(" and {} more", count - shown.len() as u64);
+    }
+    anyhow!("{count} line(s) with no digit: {message}")
+}
+
+/// Finds the calibration value of each line, using the digits and words of a [`Vocabulary`].
+#[derive(Debug, Clone)]
+pub struct Calibrator {
+    matcher: Matcher<u64>,
+}
+
+impl Calibrator {
+    /// Combines the line's first and last token into a two-digit number. Tokens may overlap, so in
+    /// `eightwo` the first is `eight` and the last is `two`. Tokens worth more than 9 contribute
+    /// the digit they'd have if written out, so `twelve` counts as `1` first and `2` last.
+    pub fn line<'a>(&self, line: &'a str) -> Option<LineCalibration<'a>> {
+        let token = |start: usize, end: usize, value: u64| CalibrationToken {
+            text: &line[start..end],
+            offset: start,
+            value,
+        };
+        let first = self.matcher.first(line.as_bytes())?;
+        let last = self.matcher.last(line.as_bytes())?;
+        Some(LineCalibration {
+            first: token(first.start, first.end, *first.value),
+            last: token(last.start, last.end, *last.value),
+            value: leading_digit(*first.value) * 10 + last.value % 10,
+        })
+    }
+
+    /// Sums the calibration value of every line, reporting all lines that have none.
+    pub fn calibrate(&self, input: &[String]) -> anyhow::Result<u64> {
+        let mut total = 0;
+        let mut missing = 0;
+        let mut shown = Vec::new();
+        for (i, line) in input.iter().enumerate() {
+            match self.line(line) {
+                Some(c) => total += c.value,
+                None => {
+                    missing += 1;
+                    if shown.len() < SHOWN {
+                        shown.push(format!("line {}: {line:?}", i + 1));
+                    }
+                }
+            }
+        }
+        if missing > 0 {
+            return Err(no_digit_error(missing, &shown));
+        }
+        Ok(total)
+    }
+
+    /// Like [`Calibrator::calibrate`], but reads `reader` a buffer at a time and never allocates per line, so
+    /// inputs of any size can be summed. Lines are split on `\n` alone.
+    pub fn calibrate_stream(&self, mut reader: impl BufRead) -> anyhow::Result<CalibrationSummary> {
+        let mut summary = CalibrationSummary::default();
+        let mut line = LineScan::default();
+        let mut missing = 0;
+        let mut shown = Vec::new();
+        let mut finish = |line: &mut LineScan, summary: &mut CalibrationSummary| {
+            summary.lines += 1;
+            match line.finish() {
+                Some(value) => summary.total += value,
+                None => {
+                    missing += 1;
+                    if shown.len() < SHOWN {
+                        shown.push(format!("line {}", summary.lines));
                     }
                 }
             }
-            if !found {
-                l = &l[1..];
+        };
+
+        loop {
+            let buf = reader.fill_buf().context("Failed to read input")?;
+            if buf.is_empty() {
+                break;
+            }
+            for byte in buf {
+                match byte {
+                    b'\n' => finish(&mut line, &mut summary),
+                    _ => line.push(&self.matcher, *byte),
+                }
             }
+            let len = buf.len();
+            summary.bytes += len as u64;
+            reader.consume(len);
+        }
+        if line.offset > 0 {
+            finish(&mut line, &mut summary);
         }
 
-        let n1 = first.unwrap() * 10;
-        let n2 = last.or(first).unwrap();
-        numbers.push(n1 + n2);
+        if missing > 0 {
+            return Err(no_digit_error(missing, &shown));
+        }
+        Ok(summary)
+    }
+
+    /// One line of trace per input line, then the total.
+    pub fn explain(&self, input: &[String]) -> String {
+        let mut out = String::new();
+        let mut total = 0;
+        for (i, line) in input.iter().enumerate() {
+            let n = i + 1;
+            match self.line(line) {
+                Some(c) => {
+                    total += c.value;
+                    out += &format!(
+                        "line {n}: first {}, last {} => {}\n",
+                        c.first, c.last, c.value
+                    );
+                }
+                None => out += &format!("line {n}: no digit in {line:?}\n"),
+            }
+        }
+        out += &format!("total: {total}\n");
+        out
+    }
+}
+
+/// Counters from [`Calibrator::calibrate_stream`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CalibrationSummary {
+    pub total: u64,
+    pub lines: u64,
+    pub bytes: u64,
+}
+
+/// The first and last token seen so far on the current line, as `(start, end, value)`.
+#[derive(Debug, Default)]
+struct LineScan {
+    state: State,
+    offset: usize,
+    first: Option<(usize, usize, u64)>,
+    last: Option<(usize, usize, u64)>,
+}
+
+impl LineScan {
+    /// Feeds one byte, applying the same tie-breaks as [`Matcher::first`] and [`Matcher::last`].
+    fn push(&mut self, matcher: &Matcher<u64>, byte: u8) {
+        self.state = matcher.step(self.state, byte);
+        self.offset += 1;
+        for (_, len, value) in matcher.matches(self.state) {
+            let token = (self.offset - len, self.offset, *value);
+            if self
+                .first
+                .is_none_or(|(start, end, _)| (token.0, Reverse(token.1)) < (start, Reverse(end)))
+            {
+                self.first = Some(token);
+            }
+            if self
+                .last
+                .is_none_or(|(start, end, _)| (token.0, token.1) > (start, end))
+            {
+                self.last = Some(token);
+            }
+        }
+    }
+
+    /// The line's calibration value, resetting for the next line.
+    fn finish(&mut self) -> Option<u64> {
+        let scan = std::mem::take(self);
+        let (_, _, first) = scan.first?;
+        let (_, _, last) = scan.last?;
+        Some(leading_digit(first) * 10 + last % 10)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lines(input: &str) -> Vec<String> {
+        input.lines().map(String::from).collect()
+    }
+
+    #[test]
+    fn test_problem1() {
+        let input = lines("1abc2\npqr3stu8vwx\na1b2c3d4e5f\ntreb7uchet");
+        assert_eq!(problem1(&input).unwrap(), 142);
+        let err = problem1(&lines("12\nabc\n3\none")).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "2 line(s) with no digit: line 2: \"abc\", line 4: \"one\""
+        );
+    }
+
+    #[test]
+    fn test_problem2() {
+        let input = lines(
+            "two1nine\neightwothree\nabcone2threexyz\nxtwone3four\n\
+             4nineeightseven2\nzoneight234\n7pqrstsixteen",
+        );
+        assert_eq!(problem2(&input).unwrap(), 281);
+        assert_eq!(problem2(&lines("eightwo")).unwrap(), 82);
+        assert_eq!(problem2(&lines("oneight")).unwrap(), 18);
+        assert_eq!(problem2(&lines("xsevenx")).unwrap(), 77);
+    }
+
+    #[test]
+    fn test_vocabularies() {
+        for name in Vocabulary::builtin_names() {
+            assert!(Vocabulary::builtin(name).unwrap().matcher().is_ok());
+        }
+        let french = Vocabulary::builtin("french").unwrap().calibrator().unwrap();
+        assert_eq!(
+            french.calibrate(&lines("xdeuxsept\nhuit")).unwrap(),
+            27 + 88
+        );
+        let german = Vocabulary::builtin("german").unwrap().calibrator().unwrap();
+        assert_eq!(german.calibrate(&lines("fünfzwei")).unwrap(), 52);
+
+        let full = Vocabulary::builtin("english-full")
+            .unwrap()
+            .calibrator()
+            .unwrap();
+        assert_eq!(full.line("twentythree").unwrap().value, 23);
+        assert_eq!(full.line("seventeen").unwrap().value, 17);
+        assert_eq!(full.line("xninety").unwrap().value, 90);
+
+        let custom: Vocabulary = "# custom\nforty two 42\n\nnil 0".parse().unwrap();
+        assert_eq!(custom.words()[0], ("forty two".to_string(), 42));
+        let calibrator = custom.calibrator().unwrap();
+        assert_eq!(calibrator.line("forty two5").unwrap().value, 45);
+        assert_eq!(calibrator.line("fortytwo"), None);
+        assert!("seven".parse::<Vocabulary>().is_err());
+        assert!("seven x".parse::<Vocabulary>().is_err());
+        assert!(Vocabulary::resolve("klingon").is_err());
+    }
+
+    #[test]
+    fn test_stream() {
+        let input = "two1nine\neightwothree\nabcone2threexyz\nxtwone3four\n\
+                     4nineeightseven2\nzoneight234\n7pqrstsixteen";
+        let calibrator = tokens(Part::Two).unwrap();
+        for capacity in [1, 3, 64] {
+            let reader = std::io::BufReader::with_capacity(capacity, input.as_bytes());
+            let summary = calibrator.calibrate_stream(reader).unwrap();
+            assert_eq!(
+                summary,
+                CalibrationSummary {
+                    total: 281,
+                    lines: 7,
+                    bytes: input.len() as u64
+                }
+            );
+        }
+        let full = Vocabulary::builtin("english-full")
+            .unwrap()
+            .calibrator()
+            .unwrap();
+        let summary = full
+            .calibrate_stream("seventeen\nxnineteen\n".as_bytes())
+            .unwrap();
+        assert_eq!((summary.total, summary.lines), (17 + 19, 2));
+
+        let err = calibrator
+            .calibrate_stream("1\nabc\n2\n\n".as_bytes())
+            .unwrap_err();
+        assert_eq!(err.to_string(), "2 line(s) with no digit: line 2, line 4");
+    }
+
+    #[test]
+    fn test_explain() {
+        let calibrator = tokens(Part::Two).unwrap();
+        let c = calibrator.line("zoneight234").unwrap();
+        assert_eq!(
+            c.first,
+            CalibrationToken {
+                text: "one",
+                offset: 1,
+                value: 1
+            }
+        );
+        assert!(!c.last.is_word());
+        assert_eq!((c.last.offset, c.value), (10, 14));
+
+        let trace = Day01
+            .explain(&lines("eightwo\nabc"), Part::Two)
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            trace,
+            "line 1: first word \"eight\" at 0, last word \"two\" at 4 => 82\n\
+             line 2: no digit in \"abc\"\n\
+             total: 82\n"
+        );
     }
-    println!("{}", numbers.iter().sum::<u64>());
 }
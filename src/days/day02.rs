@@ -1,80 +1,195 @@
-use std::str::FromStr;
+use std::{
+    collections::BTreeMap,
+    fmt,
+    str::FromStr,
+    sync::{Mutex, OnceLock},
+};
 
-use crate::util::read_input;
+use anyhow::{anyhow, bail, Context};
 
-pub fn day02() {
-    problem1();
-    problem2();
+use crate::{solution::Solution, util::parse_lines};
+
+/// The bag from the puzzle statement, used when no other limits are given.
+pub const PUZZLE_BAG: &str = "12 red, 13 green, 14 blue";
+
+pub struct Day02 {
+    /// Cubes in the bag, which limit the games possible in part 1.
+    bag: Handful,
+    /// Colours every game must show to have a non-zero power in part 2.
+    required: Vec<Color>,
+}
+
+impl Day02 {
+    pub fn new(bag: Handful, required: Vec<Color>) -> Day02 {
+        Day02 { bag, required }
+    }
+}
+
+impl Default for Day02 {
+    fn default() -> Self {
+        Day02::new(
+            PUZZLE_BAG.parse().expect("the puzzle bag is valid"),
+            Color::PUZZLE.to_vec(),
+        )
+    }
 }
 
-fn problem1() {
-    let input = read_input(2, 1);
-    let values = input
-        .lines()
-        .map(|line| Game::from_str(line))
-        .map(Result::unwrap);
+impl Solution for Day02 {
+    const DAY: usize = 2;
+
+    type Input = Vec<Game>;
+    type Answer1 = u64;
+    type Answer2 = u64;
 
-    let start: Handful = "12 red, 13 green, 14 blue".parse().unwrap();
+    fn parse(&self, input: &str) -> anyhow::Result<Self::Input> {
+        parse_lines(input)
+    }
+
+    fn part1(&self, input: &Self::Input) -> anyhow::Result<u64> {
+        Ok(input
+            .iter()
+            .filter(|game| game.is_possible(&self.bag))
+            .map(|game| game.id)
+            .sum())
+    }
 
-    let result: u64 = values
-        .filter(|game| game.is_possible(start.clone()))
-        .map(|game| game.id)
-        .sum();
-    println!("{result}");
+    fn part2(&self, input: &Self::Input) -> anyhow::Result<u64> {
+        Ok(input
+            .iter()
+            .map(|game| game.min_color_set().power(&self.required))
+            .sum())
+    }
 }
 
-fn problem2() {
-    let input = read_input(2, 2);
-    let values = input
-        .lines()
-        .map(|line| Game::from_str(line))
-        .map(Result::unwrap);
+/// A cube colour. Names are interned, so any colour can appear while copies and comparisons
+/// stay cheap. Colours are ordered by when they were interned.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Color(u32);
 
-    let result: u64 = values.map(|g| g.min_color_set().power()).sum();
-    println!("{result}");
+fn color_names() -> &'static Mutex<Vec<&'static str>> {
+    static NAMES: OnceLock<Mutex<Vec<&'static str>>> = OnceLock::new();
+    NAMES.get_or_init(|| Mutex::new(vec!["red", "green", "blue"]))
 }
 
-#[derive(Debug, PartialEq, Eq, Clone, Copy)]
-pub enum Color {
-    Red,
-    Green,
-    Blue,
+impl Color {
+    pub const RED: Color = Color(0);
+    pub const GREEN: Color = Color(1);
+    pub const BLUE: Color = Color(2);
+    /// The colours every game in the puzzle is played with.
+    pub const PUZZLE: [Color; 3] = [Color::RED, Color::GREEN, Color::BLUE];
+
+    /// The colour called `name`, interning it on first use. Interned names live for the rest
+    /// of the program.
+    pub fn new(name: &str) -> Color {
+        let mut names = color_names()
+            .lock()
+            .expect("colour table is never poisoned");
+        let index = match names.iter().position(|n| *n == name) {
+            Some(index) => index,
+            None => {
+                names.push(Box::leak(name.into()));
+                names.len() - 1
+            }
+        };
+        Color(index as u32)
+    }
+
+    pub fn name(self) -> &'static str {
+        color_names()
+            .lock()
+            .expect("colour table is never poisoned")[self.0 as usize]
+    }
+}
+
+impl fmt::Debug for Color {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Color({})", self.name())
+    }
+}
+
+impl fmt::Display for Color {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
 }
 
 impl FromStr for Color {
     type Err = anyhow::Error;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        Ok(match s {
-            "red" => Self::Red,
-            "green" => Self::Green,
-            "blue" => Self::Blue,
-            _ => anyhow::bail!("Invalid color {s}"),
-        })
+        if s.is_empty() || !s.chars().all(char::is_alphabetic) {
+            bail!("Invalid color {s}");
+        }
+        Ok(Color::new(s))
     }
 }
 
-#[derive(Debug, Clone, Default)]
+/// Cube counts by colour. Colours that aren't present count as zero.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
 pub struct Handful {
-    pub red: u64,
-    pub green: u64,
-    pub blue: u64,
+    counts: BTreeMap<Color, u64>,
 }
 
 impl Handful {
+    pub fn get(&self, color: Color) -> u64 {
+        self.counts.get(&color).copied().unwrap_or(0)
+    }
+
+    pub fn add(&mut self, color: Color, count: u64) {
+        *self.counts.entry(color).or_default() += count;
+    }
+
+    /// Every colour mentioned with its count, in interning order.
+    pub fn iter(&self) -> impl Iterator<Item = (Color, u64)> + '_ {
+        self.counts.iter().map(|(color, count)| (*color, *count))
+    }
+
     pub fn contains(&self, other: &Handful) -> bool {
-        self.red >= other.red && self.green >= other.green && self.blue >= other.blue
+        other.iter().all(|(color, count)| self.get(color) >= count)
     }
 
-    pub fn power(&self) -> u64 {
-        self.red * self.green * self.blue
+    /// The product of the counts of every colour mentioned. The `required` colours always take
+    /// part, so a handful missing any of them has power 0.
+    pub fn power(&self, required: &[Color]) -> u64 {
+        if required
+            .iter()
+            .any(|color| !self.counts.contains_key(color))
+        {
+            return 0;
+        }
+        self.counts.values().product()
     }
 
     pub fn aggregate_min(&mut self, other: &Handful) {
-        let Handful { red, green, blue } = other;
-        self.red = self.red.max(*red);
-        self.blue = self.blue.max(*blue);
-        self.green = self.green.max(*green);
+        for (color, count) in other.iter() {
+            let entry = self.counts.entry(color).or_default();
+            *entry = (*entry).max(count);
+        }
+    }
+}
+
+impl FromIterator<(Color, u64)> for Handful {
+    fn from_iter<I: IntoIterator<Item = (Color, u64)>>(iter: I) -> Self {
+        let mut handful = Handful::default();
+        for (color, count) in iter {
+            handful.add(color, count);
+        }
+        handful
+    }
+}
+
+/// Formats as the puzzle does, e.g. `3 blue, 4 red`, with colours ordered by name.
+impl fmt::Display for Handful {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut counts: Vec<(Color, u64)> = self.iter().collect();
+        counts.sort_by_cached_key(|(color, _)| color.name());
+        for (i, (color, count)) in counts.into_iter().enumerate() {
+            if i > 0 {
+                f.write_str(", ")?;
+            }
+            write!(f, "{count} {color}")?;
+        }
+        Ok(())
     }
 }
 
@@ -82,39 +197,49 @@ impl FromStr for Handful {
     type Err = anyhow::Error;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let results = s
-            .split(", ")
-            .map(|s| s.trim())
-            .map(|s| {
-                s.split_once(' ').map(|(count, color)| {
-                    (
-                        u64::from_str_radix(count, 10).unwrap(),
-                        Color::from_str(color).unwrap(),
-                    )
-                })
-            })
-            .map(Option::unwrap);
         let mut handful = Handful::default();
-        for (count, color) in results {
-            match color {
-                Color::Red => handful.red += count,
-                Color::Green => handful.green += count,
-                Color::Blue => handful.blue += count,
-            }
+        for cubes in s.split(", ").map(|s| s.trim()) {
+            let (count, color) = cubes
+                .split_once(' ')
+                .ok_or_else(|| anyhow!("Invalid cubes `{cubes}`"))?;
+            let count: u64 = count
+                .parse()
+                .with_context(|| format!("Invalid count in `{cubes}`"))?;
+            handful.add(Color::from_str(color)?, count);
         }
 
         Ok(handful)
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Game {
     id: u64,
     results: Vec<Handful>,
 }
 
 impl Game {
-    pub fn is_possible(&self, totals: Handful) -> bool {
+    /// A game with at least one handful, each showing at least one colour, so that it can be
+    /// written out and parsed back.
+    pub fn new(id: u64, results: Vec<Handful>) -> anyhow::Result<Game> {
+        if results.is_empty() {
+            bail!("Game {id} has no handfuls");
+        }
+        if results.iter().any(|handful| handful.counts.is_empty()) {
+            bail!("Game {id} has an empty handful");
+        }
+        Ok(Game { id, results })
+    }
+
+    pub fn id(&self) -> u64 {
+        self.id
+    }
+
+    pub fn results(&self) -> &[Handful] {
+        &self.results
+    }
+
+    pub fn is_possible(&self, totals: &Handful) -> bool {
         for handful in &self.results {
             if !totals.contains(handful) {
                 return false;
@@ -132,16 +257,169 @@ impl Game {
     }
 }
 
+/// Formats as the puzzle does, e.g. `Game 1: 3 blue, 4 red; 2 green`.
+impl fmt::Display for Game {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Game {}:", self.id)?;
+        for (i, handful) in self.results.iter().enumerate() {
+            let separator = if i > 0 { ";" } else { "" };
+            write!(f, "{separator} {handful}")?;
+        }
+        Ok(())
+    }
+}
+
 impl FromStr for Game {
     type Err = anyhow::Error;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let (game_id, handfuls) = s.split_once(':').unwrap();
-        let id: u64 = game_id.strip_prefix("Game ").unwrap().parse().unwrap();
+        let (game_id, handfuls) = s.split_once(':').ok_or(anyhow!("Missing `:`"))?;
+        let id: u64 = game_id
+            .strip_prefix("Game ")
+            .ok_or(anyhow!("Missing `Game` prefix"))?
+            .parse()
+            .with_context(|| format!("Invalid game id `{game_id}`"))?;
         let results: Vec<Handful> = handfuls
             .split("; ")
-            .map(|h| Handful::from_str(h).unwrap())
-            .collect();
-        Ok(Game { id, results })
+            .map(Handful::from_str)
+            .collect::<Result<_, _>>()?;
+        Game::new(id, results)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = "Game 1: 3 blue, 4 red; 1 red, 2 green, 6 blue; 2 green\n\
+                           Game 2: 1 blue, 2 green; 3 green, 4 blue, 1 red; 1 green, 1 blue\n\
+                           Game 3: 8 green, 6 blue, 20 red; 5 blue, 4 red, 13 green; 5 green, 1 red\n\
+                           Game 4: 1 green, 3 red, 6 blue; 3 green, 6 red; 3 green, 15 blue, 14 red\n\
+                           Game 5: 6 red, 1 blue, 3 green; 2 blue, 1 red, 2 green";
+
+    #[test]
+    fn test_parse_errors() {
+        let game = Game::from_str("Game 3: 8 green, 6 blue, 20 red; 5 blue, 4 red").unwrap();
+        assert_eq!(game.id, 3);
+        assert_eq!(game.min_color_set().power(&Color::PUZZLE), 8 * 6 * 20);
+
+        assert!(Game::from_str("Game 3 8 green").is_err());
+        assert!(Game::from_str("Game x: 8 green").is_err());
+        assert!(Game::from_str("Game 3: 8green").is_err());
+        assert!(Game::from_str("Game 3: 8 dark red").is_err());
+        assert!(Game::from_str("Game 3: 8 r3d").is_err());
+    }
+
+    #[test]
+    fn test_colors() {
+        let teal: Color = "teal".parse().unwrap();
+        assert_eq!(teal, Color::new("teal"));
+        assert_eq!(teal.name(), "teal");
+        assert_eq!("red".parse::<Color>().unwrap(), Color::RED);
+        assert_ne!(teal, Color::BLUE);
+
+        let game = Game::from_str("Game 7: 2 teal, 1 red; 5 teal; 3 blue, 1 red, 1 red").unwrap();
+        let min = game.min_color_set();
+        assert_eq!(min.get(teal), 5);
+        assert_eq!(min.get(Color::RED), 2);
+        assert_eq!(min.get(Color::GREEN), 0);
+        assert_eq!(min.power(&Color::PUZZLE), 0);
+        assert_eq!(min.power(&[teal, Color::RED]), 5 * 2 * 3);
+        assert_eq!(Handful::default().power(&Color::PUZZLE), 0);
+
+        let bag: Handful = "12 red, 13 green, 14 blue".parse().unwrap();
+        assert!(!game.is_possible(&bag));
+        let mut with_teal = bag.clone();
+        with_teal.add(teal, 5);
+        assert!(game.is_possible(&with_teal));
+
+        let game = Game::from_str("Game 8: 2 teal, 1 red; 4 green, 3 blue").unwrap();
+        assert_eq!(game.min_color_set().power(&Color::PUZZLE), 2 * 4 * 3);
+        let day = Day02::new(bag.clone(), Color::PUZZLE.to_vec());
+        assert_eq!(day.part2(&vec![game]).unwrap(), 2 * 4 * 3);
+
+        // Games played without the puzzle's colours only score when those aren't required.
+        let games = vec![Game::from_str("Game 1: 3 teal, 4 mauve; 2 teal").unwrap()];
+        assert_eq!(day.part2(&games).unwrap(), 0);
+        let mauve = Color::new("mauve");
+        let day = Day02::new(bag, vec![teal, mauve]);
+        assert_eq!(day.part2(&games).unwrap(), 3 * 4);
+    }
+
+    #[test]
+    fn test_display() {
+        assert_eq!(Color::GREEN.to_string(), "green");
+        let game =
+            Game::from_str("Game 12: 3 blue, 4 red; 1 red, 2 green, 6 blue; 2 green").unwrap();
+        assert_eq!(
+            game.to_string(),
+            "Game 12: 3 blue, 4 red; 6 blue, 2 green, 1 red; 2 green"
+        );
+        assert_eq!(game.results()[2].to_string(), "2 green");
+
+        // Order doesn't depend on when a colour was first seen.
+        let late: Handful = "1 zinc, 2 amber, 3 red".parse().unwrap();
+        assert_eq!(late.to_string(), "2 amber, 3 red, 1 zinc");
+
+        assert!(Game::new(1, vec![]).is_err());
+        assert!(Game::new(1, vec![Handful::default()]).is_err());
+    }
+
+    /// A small deterministic generator, so the round-trip test needs no extra dependencies.
+    struct Lcg(u64);
+
+    impl Lcg {
+        fn below(&mut self, n: u64) -> u64 {
+            self.0 = self
+                .0
+                .wrapping_mul(6364136223846793005)
+                .wrapping_add(1442695040888963407);
+            (self.0 >> 33) % n
+        }
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let palette = ["red", "green", "blue", "teal", "mauve", "ochre"].map(Color::new);
+        let mut rng = Lcg(2023);
+        for id in 1..=500 {
+            let results = (0..=rng.below(5))
+                .map(|_| {
+                    (0..=rng.below(palette.len() as u64))
+                        .map(|_| (palette[rng.below(6) as usize], rng.below(21)))
+                        .collect()
+                })
+                .collect();
+            let game = Game::new(id, results).unwrap();
+            let text = game.to_string();
+            let parsed: Game = text.parse().unwrap();
+            assert_eq!(parsed, game, "{text}");
+            assert_eq!(parsed.to_string(), text);
+        }
+
+        let games = Day02::default().parse(EXAMPLE).unwrap();
+        for game in games {
+            assert_eq!(game.to_string().parse::<Game>().unwrap(), game);
+        }
+    }
+
+    #[test]
+    fn test_solution() {
+        let games = Day02::default().parse(EXAMPLE).unwrap();
+        assert_eq!(Day02::default().part1(&games).unwrap(), 8);
+        assert_eq!(Day02::default().part2(&games).unwrap(), 2286);
+
+        let generous = Day02::new(
+            "20 red, 20 green, 20 blue".parse().unwrap(),
+            Color::PUZZLE.to_vec(),
+        );
+        assert_eq!(generous.part1(&games).unwrap(), 15);
+
+        // Part 2 doesn't depend on the bag.
+        let with_teal = Day02::new(
+            "12 red, 13 green, 14 blue, 3 teal".parse().unwrap(),
+            Color::PUZZLE.to_vec(),
+        );
+        assert_eq!(with_teal.part2(&games).unwrap(), 2286);
     }
 }
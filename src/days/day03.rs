@@ -1,44 +1,44 @@
 use std::{
-    collections::{HashMap, HashSet},
-    ops::Deref,
+    collections::HashMap,
+    ops::{Range, RangeInclusive},
     str::FromStr,
 };
 
-use crate::util::read_input;
+use anyhow::Context;
 
-pub fn day03() {
-    problem1();
-    problem2();
-}
+use crate::{
+    dir::Dir,
+    grid::Grid,
+    image::{Image, Rgb},
+    solution::Solution,
+    term::Style,
+};
 
-fn problem1() {
-    let input = read_input(3, 1);
-    let schematic = Schematic::from_str(&input).unwrap();
-    let part_num_sum: u64 = schematic.part_numbers().into_iter().sum();
-    println!("{part_num_sum}");
-}
+pub struct Day03;
 
-fn problem2() {
-    let input = read_input(3, 2);
-    let schematic = Schematic::from_str(&input).unwrap();
-    let gear_ratios: u64 = schematic
-        .gear_ratios()
-        .into_iter()
-        .map(|(a, b)| a * b)
-        .sum();
-    println!("{gear_ratios}");
-}
+impl Solution for Day03 {
+    const DAY: usize = 3;
+    const EXAMPLE_ANSWERS: [Option<&'static str>; 2] = [Some("4361"), Some("467835")];
 
-#[derive(Debug, Clone, Copy)]
-pub enum Dir {
-    Up,
-    Down,
-    Left,
-    Right,
-    TopLeft,
-    TopRight,
-    BottomLeft,
-    BottomRight,
+    type Input = Schematic;
+    type Answer1 = u64;
+    type Answer2 = u64;
+
+    fn parse(&self, input: &str) -> anyhow::Result<Self::Input> {
+        Schematic::from_str(input)
+    }
+
+    fn part1(&self, schematic: &Schematic) -> anyhow::Result<u64> {
+        Ok(schematic.part_numbers().into_iter().sum())
+    }
+
+    fn part2(&self, schematic: &Schematic) -> anyhow::Result<u64> {
+        Ok(schematic
+            .gears(&GearSpec::default())
+            .iter()
+            .map(|gear| gear.ratio)
+            .sum())
+    }
 }
 
 #[derive(Debug, Clone, Copy)]
@@ -58,203 +58,309 @@ impl Cell {
     }
 
     pub fn is_number(&self) -> bool {
+        matches!(self, Cell::Number(_))
+    }
+
+    pub fn to_char(self) -> char {
         match self {
-            Cell::Number(_) => true,
-            _ => false,
+            Cell::Nothing => '.',
+            Cell::Number(c) | Cell::Symbol(c) => c,
         }
     }
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
-pub struct Pair(usize, usize);
+/// How a cell is drawn, based on its role in the puzzle.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CellKind {
+    Empty,
+    /// A digit of a number touching a symbol.
+    PartNumber,
+    /// A digit of a number touching no symbol.
+    IgnoredNumber,
+    Symbol,
+    Gear,
+}
 
-impl Pair {
-    pub fn new(x: usize, y: usize) -> Self {
-        Pair(x, y)
+impl CellKind {
+    pub fn style(self) -> Style {
+        match self {
+            CellKind::Empty => Style::Dim,
+            CellKind::PartNumber => Style::Green,
+            CellKind::IgnoredNumber => Style::Red,
+            CellKind::Symbol => Style::Yellow,
+            CellKind::Gear => Style::BoldMagenta,
+        }
     }
 
-    pub fn adjacent_dir(&self, dir: Dir) -> Option<Pair> {
-        let x = match dir {
-            Dir::Up => self.0,
-            Dir::Down => self.0,
-            Dir::Left => self.0.checked_sub(1)?,
-            Dir::Right => self.0 + 1,
-            Dir::TopLeft => self.0.checked_sub(1)?,
-            Dir::TopRight => self.0 + 1,
-            Dir::BottomLeft => self.0.checked_sub(1)?,
-            Dir::BottomRight => self.0 + 1,
-        };
-
-        let y = match dir {
-            Dir::Up => self.1.checked_sub(1)?,
-            Dir::Down => self.1 + 1,
-            Dir::Left => self.1,
-            Dir::Right => self.1,
-            Dir::TopLeft => self.1.checked_sub(1)?,
-            Dir::TopRight => self.1.checked_sub(1)?,
-            Dir::BottomLeft => self.1 + 1,
-            Dir::BottomRight => self.1 + 1,
-        };
-
-        Some(Pair::new(x, y))
+    /// The image counterpart of [`CellKind::style`].
+    pub fn rgb(self) -> Rgb {
+        match self {
+            CellKind::Empty => Rgb::GREY,
+            CellKind::PartNumber => Rgb::GREEN,
+            CellKind::IgnoredNumber => Rgb::RED,
+            CellKind::Symbol => Rgb::YELLOW,
+            CellKind::Gear => Rgb::MAGENTA,
+        }
     }
+}
 
-    pub fn adjacent(&self) -> HashSet<Pair> {
-        [
-            Dir::Up,
-            Dir::Down,
-            Dir::Left,
-            Dir::Right,
-            Dir::TopLeft,
-            Dir::TopRight,
-            Dir::BottomLeft,
-            Dir::BottomRight,
-        ]
-        .into_iter()
-        .map(|dir| self.adjacent_dir(dir))
-        .filter_map(|p| p)
-        .collect()
-    }
+#[derive(Debug, Clone, Default)]
+pub struct RenderOptions {
+    pub gears: GearSpec,
+    /// Inclusive corners of the area to draw; the whole schematic when `None`.
+    pub crop: Option<((usize, usize), (usize, usize))>,
+    pub color: bool,
+}
+
+/// A run of digits on one row, covering columns `span`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PartNumber {
+    pub value: u64,
+    pub row: usize,
+    pub span: Range<usize>,
 }
 
 #[derive(Debug, Clone, Default)]
 pub struct Schematic {
-    grid: Vec<Vec<Cell>>,
+    grid: Grid<Cell>,
+    numbers: Vec<PartNumber>,
+    /// Symbols touching each number, parallel to `numbers`.
+    number_symbols: Vec<Vec<(usize, usize)>>,
+    /// Indices into `numbers` of the numbers touching each symbol.
+    symbol_numbers: HashMap<(usize, usize), Vec<usize>>,
 }
 
 impl Schematic {
-    pub fn is_symbol(&self, coord: Pair) -> bool {
-        let line = match self.grid.get(coord.1) {
-            Some(line) => line,
-            None => return false,
+    /// Indexes every number and the symbols around it in a single pass over the grid.
+    pub fn new(grid: Grid<Cell>) -> anyhow::Result<Schematic> {
+        let mut schematic = Schematic {
+            grid,
+            ..Default::default()
         };
-        match line.get(coord.0) {
-            Some(Cell::Symbol(_)) => true,
-            _ => false,
+        let regions = schematic
+            .grid
+            .regions(&[Dir::Left, Dir::Right], Cell::is_number);
+        for region in regions {
+            let digits: String = region
+                .cells
+                .iter()
+                .filter_map(|cell| match schematic.grid[*cell] {
+                    Cell::Number(n) => Some(n),
+                    _ => None,
+                })
+                .collect();
+            let value = digits
+                .parse()
+                .with_context(|| format!("Invalid number at {:?}", region.min))?;
+
+            let symbols: Vec<(usize, usize)> = schematic
+                .grid
+                .region_neighbours(&region, &Dir::ALL)
+                .into_iter()
+                .filter(|cell| matches!(schematic.grid[*cell], Cell::Symbol(_)))
+                .collect();
+            let index = schematic.numbers.len();
+            for symbol in &symbols {
+                schematic
+                    .symbol_numbers
+                    .entry(*symbol)
+                    .or_default()
+                    .push(index);
+            }
+            schematic.numbers.push(PartNumber {
+                value,
+                row: region.min.1,
+                span: region.min.0..region.max.0 + 1,
+            });
+            schematic.number_symbols.push(symbols);
         }
+        Ok(schematic)
     }
 
-    pub fn is_asterisk(&self, coord: Pair) -> bool {
-        let line = match self.grid.get(coord.1) {
-            Some(line) => line,
-            None => return false,
-        };
-        match line.get(coord.0) {
-            Some(Cell::Symbol('*')) => true,
-            _ => false,
-        }
+    pub fn grid(&self) -> &Grid<Cell> {
+        &self.grid
     }
 
-    pub fn part_numbers(&self) -> Vec<u64> {
-        let mut parts = Vec::new();
-        for (y, line) in self.grid.iter().enumerate() {
-            let mut digits = Vec::new();
-            let mut symbol_adjacent = false;
-            for (x, cell) in line.iter().enumerate() {
-                if let Cell::Number(n) = cell {
-                    digits.push(n);
-                    let adjacent = Pair::new(x, y).adjacent();
-                    let ad = adjacent.iter().any(|p| self.is_symbol(*p));
-                    symbol_adjacent = ad || symbol_adjacent;
-                    continue;
-                }
-                if symbol_adjacent && digits.len() > 0 {
-                    let s: String = digits.iter().map(Deref::deref).collect();
-                    let n: u64 = s.parse().unwrap();
-                    parts.push(n);
+    /// Every number in the schematic, in reading order.
+    pub fn numbers(&self) -> &[PartNumber] {
+        &self.numbers
+    }
+
+    /// The index of the number covering `(x, y)`, if any. Numbers are in reading order, so the
+    /// candidate is the last one starting at or before `(x, y)`.
+    pub fn number_at(&self, x: usize, y: usize) -> Option<usize> {
+        let index = self
+            .numbers
+            .partition_point(|n| (n.row, n.span.start) <= (y, x))
+            .checked_sub(1)?;
+        let n = &self.numbers[index];
+        (n.row == y && n.span.contains(&x)).then_some(index)
+    }
+
+    /// Numbers touching the symbol at `(x, y)`, including diagonally.
+    pub fn numbers_adjacent_to(&self, x: usize, y: usize) -> impl Iterator<Item = &PartNumber> {
+        self.symbol_numbers
+            .get(&(x, y))
+            .into_iter()
+            .flatten()
+            .map(|i| &self.numbers[*i])
+    }
+
+    /// Symbols touching the number at `index` in [`Schematic::numbers`].
+    pub fn symbols_adjacent_to(&self, index: usize) -> &[(usize, usize)] {
+        self.number_symbols
+            .get(index)
+            .map_or(&[], |symbols| symbols.as_slice())
+    }
+
+    /// The display role of every cell, treating symbols matching `spec` as gears.
+    pub fn classify(&self, spec: &GearSpec) -> Grid<CellKind> {
+        let mut kinds = self.grid.map(|cell| match cell {
+            Cell::Nothing => CellKind::Empty,
+            Cell::Number(_) => CellKind::IgnoredNumber,
+            Cell::Symbol(_) => CellKind::Symbol,
+        });
+        for (number, symbols) in self.numbers.iter().zip(&self.number_symbols) {
+            if !symbols.is_empty() {
+                for x in number.span.clone() {
+                    kinds[(x, number.row)] = CellKind::PartNumber;
                 }
-                digits.clear();
-                symbol_adjacent = false;
             }
-            if symbol_adjacent && digits.len() > 0 {
-                let s: String = digits.iter().map(Deref::deref).collect();
-                let n: u64 = s.parse().unwrap();
-                parts.push(n);
-            }
-            digits.clear();
         }
-        return parts;
+        for gear in self.gears(spec) {
+            kinds[gear.position] = CellKind::Gear;
+        }
+        kinds
     }
 
-    pub fn gear_ratios(&self) -> Vec<(u64, u64)> {
-        let mut parts = Vec::new();
-        for (y, line) in self.grid.iter().enumerate() {
-            let mut digits = Vec::new();
-            let mut asterisks = HashSet::new();
-            for (x, cell) in line.iter().enumerate() {
-                if let Cell::Number(n) = cell {
-                    digits.push(n);
-                    let adjacent = Pair::new(x, y).adjacent();
-                    for possible in adjacent.iter() {
-                        if self.is_asterisk(*possible) {
-                            asterisks.insert(*possible);
-                        }
-                    }
-                    continue;
-                }
-                if asterisks.len() > 0 && digits.len() > 0 {
-                    let s: String = digits.iter().map(Deref::deref).collect();
-                    let n: u64 = s.parse().unwrap();
-                    for asterisk in asterisks.clone() {
-                        parts.push((asterisk, n));
-                    }
-                }
-                digits.clear();
-                asterisks.clear();
-            }
-            if asterisks.len() > 0 && digits.len() > 0 {
-                let s: String = digits.iter().map(Deref::deref).collect();
-                let n: u64 = s.parse().unwrap();
-                for asterisk in asterisks {
-                    parts.push((asterisk, n));
-                }
+    /// Draws the schematic one row per line, colouring cells by [`CellKind`] if enabled.
+    pub fn render(&self, options: &RenderOptions) -> String {
+        let mut cells = self.grid.map(|cell| cell.to_char());
+        let mut kinds = self.classify(&options.gears);
+        if let Some((min, max)) = options.crop {
+            cells = cells.crop(min, max);
+            kinds = kinds.crop(min, max);
+        }
+
+        let mut out = String::new();
+        for (chars, kinds) in cells.rows().zip(kinds.rows()) {
+            for (c, kind) in chars.iter().zip(kinds) {
+                out.push_str(&kind.style().paint(&c.to_string(), options.color));
             }
-            digits.clear();
+            out.push('\n');
         }
-        let mut parts_map = HashMap::new();
-        for (asterisk, number) in parts {
-            let v: &mut Vec<u64> = parts_map
-                .entry(asterisk)
-                .or_insert_with(|| Vec::new())
-                .as_mut();
-            v.push(number);
+        out
+    }
+
+    /// Draws each cell as a `scale`-pixel square coloured by its [`CellKind`].
+    pub fn image(
+        &self,
+        spec: &GearSpec,
+        scale: usize,
+        color: impl FnMut(&CellKind) -> Rgb,
+    ) -> anyhow::Result<Image> {
+        Image::from_grid(&self.classify(spec), scale, color)
+    }
+
+    pub fn part_numbers(&self) -> Vec<u64> {
+        self.numbers
+            .iter()
+            .zip(&self.number_symbols)
+            .filter(|(_, symbols)| !symbols.is_empty())
+            .map(|(number, _)| number.value)
+            .collect()
+    }
+
+    /// Every symbol matching `spec`, in reading order.
+    pub fn gears(&self, spec: &GearSpec) -> Vec<Gear> {
+        let mut gears: Vec<Gear> = self
+            .symbol_numbers
+            .iter()
+            .filter_map(|(&position, numbers)| {
+                let Cell::Symbol(symbol) = self.grid[position] else {
+                    return None;
+                };
+                if !spec.symbols.contains(&symbol) || !spec.neighbours.contains(&numbers.len()) {
+                    return None;
+                }
+                let numbers: Vec<u64> = numbers.iter().map(|i| self.numbers[*i].value).collect();
+                Some(Gear {
+                    position,
+                    symbol,
+                    ratio: spec.combine.apply(&numbers),
+                    numbers,
+                })
+            })
+            .collect();
+        gears.sort_by_key(|gear| (gear.position.1, gear.position.0));
+        gears
+    }
+}
+
+/// Which symbols count as gears and how their neighbouring numbers combine.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GearSpec {
+    pub symbols: Vec<char>,
+    /// How many numbers must touch the symbol.
+    pub neighbours: RangeInclusive<usize>,
+    pub combine: Combine,
+}
+
+impl Default for GearSpec {
+    /// The puzzle's rule: a `*` touching exactly two numbers, multiplied together.
+    fn default() -> Self {
+        GearSpec {
+            symbols: vec!['*'],
+            neighbours: 2..=2,
+            combine: Combine::Product,
         }
+    }
+}
 
-        let mut parts = Vec::new();
-        for (_asterisk, ast_parts) in parts_map {
-            if ast_parts.len() == 2 {
-                parts.push((ast_parts[0], ast_parts[1]));
-            }
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Combine {
+    Product,
+    Sum,
+}
+
+impl Combine {
+    pub fn apply(self, values: &[u64]) -> u64 {
+        match self {
+            Combine::Product => values.iter().product(),
+            Combine::Sum => values.iter().sum(),
         }
-        return parts;
     }
 }
 
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Gear {
+    pub position: (usize, usize),
+    pub symbol: char,
+    /// Touching numbers in reading order.
+    pub numbers: Vec<u64>,
+    pub ratio: u64,
+}
+
 impl FromStr for Schematic {
     type Err = anyhow::Error;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let mut schematic = Schematic::default();
-        for line in s.lines() {
-            let mut schematic_line = Vec::new();
-            for ch in line.chars() {
-                schematic_line.push(Cell::from_char(ch));
-            }
-            schematic.grid.push(schematic_line);
-        }
-        Ok(schematic)
+        Schematic::new(Grid::parse(s, Cell::from_char)?)
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::util::read_example;
+    use crate::{
+        solution::{DynSolution, Part},
+        util::read_example,
+    };
 
     use super::*;
 
     #[test]
     fn test_problem1() {
-        let input = read_example(3, 1);
+        let input = read_example(3, 1).unwrap();
         let schematic: Schematic = input.parse().unwrap();
         let part_number_sum: u64 = schematic.part_numbers().iter().sum();
         println!("{schematic:?}");
@@ -263,11 +369,123 @@ mod tests {
 
     #[test]
     fn test_problem2() {
-        let input = read_example(3, 1);
+        let input = read_example(3, 1).unwrap();
         let schematic = Schematic::from_str(&input).unwrap();
-        let gear_ratios = schematic.gear_ratios();
-        println!("{gear_ratios:?}");
-        let total: u64 = gear_ratios.into_iter().map(|(a, b)| a * b).sum();
+        let gears = schematic.gears(&GearSpec::default());
+        println!("{gears:?}");
+        assert_eq!(
+            gears[0],
+            Gear {
+                position: (3, 1),
+                symbol: '*',
+                numbers: vec![467, 35],
+                ratio: 16345
+            }
+        );
+        let total: u64 = gears.iter().map(|g| g.ratio).sum();
         assert_eq!(total, 467835);
     }
+
+    #[test]
+    fn test_gear_variants() {
+        let input = read_example(3, 1).unwrap();
+        let schematic = Schematic::from_str(&input).unwrap();
+
+        let lonely_stars = GearSpec {
+            neighbours: 1..=1,
+            ..Default::default()
+        };
+        let gears = schematic.gears(&lonely_stars);
+        assert_eq!(gears.len(), 1);
+        assert_eq!(gears[0].numbers, [617]);
+
+        let any_symbol_sum = GearSpec {
+            symbols: vec!['*', '#', '+', '$'],
+            neighbours: 1..=usize::MAX,
+            combine: Combine::Sum,
+        };
+        let total: u64 = schematic
+            .gears(&any_symbol_sum)
+            .iter()
+            .map(|g| g.ratio)
+            .sum();
+        assert_eq!(total, 4361);
+    }
+
+    #[test]
+    fn test_numbers() {
+        let input = read_example(3, 1).unwrap();
+        let schematic = Schematic::from_str(&input).unwrap();
+        let numbers = schematic.numbers();
+        assert_eq!(numbers.len(), 10);
+        assert_eq!(
+            numbers[0],
+            PartNumber {
+                value: 467,
+                row: 0,
+                span: 0..3
+            }
+        );
+        assert_eq!(schematic.symbols_adjacent_to(0), [(3, 1)]);
+
+        let lone = schematic.number_at(6, 0).unwrap();
+        assert_eq!(numbers[lone].value, 114);
+        assert!(schematic.symbols_adjacent_to(lone).is_empty());
+        assert_eq!(schematic.number_at(3, 0), None);
+        assert_eq!(schematic.number_at(2, 0), Some(0));
+        assert_eq!(schematic.number_at(0, 1), None);
+        assert_eq!(numbers[schematic.number_at(7, 9).unwrap()].value, 598);
+        assert_eq!(schematic.number_at(9, 9), None);
+
+        let mut gear: Vec<u64> = schematic
+            .numbers_adjacent_to(3, 1)
+            .map(|n| n.value)
+            .collect();
+        gear.sort();
+        assert_eq!(gear, [35, 467]);
+        assert_eq!(schematic.numbers_adjacent_to(0, 0).count(), 0);
+    }
+
+    #[test]
+    fn test_render() {
+        let input = read_example(3, 1).unwrap();
+        let schematic = Schematic::from_str(&input).unwrap();
+
+        let plain = schematic.render(&RenderOptions::default());
+        assert_eq!(plain.trim_end(), input.trim_end());
+
+        let cropped = schematic.render(&RenderOptions {
+            crop: Some(((0, 0), (3, 1))),
+            ..Default::default()
+        });
+        assert_eq!(cropped, "467.\n...*\n");
+
+        let colored = schematic.render(&RenderOptions {
+            crop: Some(((2, 1), (3, 1))),
+            color: true,
+            ..Default::default()
+        });
+        assert_eq!(colored, "\x1b[2m.\x1b[0m\x1b[1;35m*\x1b[0m\n");
+
+        let kinds = schematic.classify(&GearSpec::default());
+        assert_eq!(kinds[(0, 0)], CellKind::PartNumber);
+        assert_eq!(kinds[(5, 0)], CellKind::IgnoredNumber);
+        assert_eq!(kinds[(6, 3)], CellKind::Symbol);
+        assert_eq!(kinds[(3, 1)], CellKind::Gear);
+
+        let image = schematic
+            .image(&GearSpec::default(), 3, |kind| kind.rgb())
+            .unwrap();
+        assert_eq!((image.width(), image.height()), (30, 30));
+        assert_eq!(image.pixel(10, 4), Some(Rgb::MAGENTA));
+        assert_eq!(image.pixel(16, 2), Some(Rgb::RED));
+    }
+
+    #[test]
+    fn test_solution() {
+        let input = read_example(3, 1).unwrap();
+        let answers = Day03.solve(&input, &Part::ALL).unwrap();
+        assert_eq!(answers.part1.as_deref(), Some("4361"));
+        assert_eq!(answers.part2.as_deref(), Some("467835"));
+    }
 }
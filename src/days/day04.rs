@@ -5,25 +5,29 @@ use std::{
 
 use anyhow::anyhow;
 
-use crate::util::read_input;
+use crate::{solution::Solution, util::parse_lines};
 
-pub fn day04() {
-    problem1();
-    problem2();
-}
+pub struct Day04;
 
-fn problem1() {
-    let input = read_input(4, 1);
-    let cards: Vec<Card> = input.lines().map(|l| Card::from_str(l).unwrap()).collect();
-    let points: u64 = cards.iter().map(Card::points).sum();
-    println!("{points}");
-}
+impl Solution for Day04 {
+    const DAY: usize = 4;
+    const EXAMPLE_ANSWERS: [Option<&'static str>; 2] = [Some("13"), Some("30")];
+
+    type Input = Vec<Card>;
+    type Answer1 = u64;
+    type Answer2 = u64;
+
+    fn parse(&self, input: &str) -> anyhow::Result<Self::Input> {
+        parse_lines(input)
+    }
+
+    fn part1(&self, cards: &Self::Input) -> anyhow::Result<u64> {
+        Ok(cards.iter().map(Card::points).sum())
+    }
 
-fn problem2() {
-    let input = read_input(4, 1);
-    let cards: Vec<Card> = input.lines().map(|l| Card::from_str(l).unwrap()).collect();
-    let result = queue_executor(&cards);
-    println!("{result}");
+    fn part2(&self, cards: &Self::Input) -> anyhow::Result<u64> {
+        queue_executor(cards)
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -35,9 +39,7 @@ pub struct Card {
 
 impl Card {
     pub fn winning_numbers(&self) -> usize {
-        let union = self.winners.intersection(&self.have);
-        let c = union.count();
-        c
+        self.winners.intersection(&self.have).count()
     }
 
     pub fn points(&self) -> u64 {
@@ -63,14 +65,14 @@ impl FromStr for Card {
         let (winners, have) = hand.split_once(" | ").ok_or(anyhow!("Missing hand"))?;
         let winners: anyhow::Result<HashSet<u64>> = winners
             .split(' ')
-            .filter(|s| s.len() > 0)
+            .filter(|s| !s.is_empty())
             .map(|n| Ok(n.parse()?))
             .collect();
         let winners = winners?;
 
         let have: anyhow::Result<HashSet<u64>> = have
             .split(' ')
-            .filter(|s| s.len() > 0)
+            .filter(|s| !s.is_empty())
             .map(|n| Ok(n.parse()?))
             .collect();
         let have = have?;
@@ -79,7 +81,7 @@ impl FromStr for Card {
     }
 }
 
-fn queue_executor(cards: &[Card]) -> u64 {
+fn queue_executor(cards: &[Card]) -> anyhow::Result<u64> {
     let mut queue: VecDeque<Card> = cards.iter().cloned().collect();
     let mut counter: u64 = 0;
     while let Some(card) = queue.pop_front() {
@@ -88,22 +90,37 @@ fn queue_executor(cards: &[Card]) -> u64 {
         if winners > 0 {
             let id = card.id as usize;
             let r = id..(id + winners);
-            let next_set = cards[r].iter().cloned();
+            let next_set = cards
+                .get(r.clone())
+                .ok_or_else(|| {
+                    anyhow!(
+                        "Card {} wins cards {}..{} but there are only {} cards",
+                        card.id,
+                        r.start + 1,
+                        r.end,
+                        cards.len()
+                    )
+                })?
+                .iter()
+                .cloned();
             queue.extend(next_set);
         }
     }
-    counter
+    Ok(counter)
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::util::read_example;
+    use crate::{
+        solution::{DynSolution, Part},
+        util::read_example,
+    };
 
     use super::*;
 
     #[test]
     fn test_problem1() {
-        let input = read_example(4, 1);
+        let input = read_example(4, 1).unwrap();
         let cards: u64 = input
             .lines()
             .map(|line| Card::from_str(line).unwrap())
@@ -114,12 +131,29 @@ mod tests {
 
     #[test]
     fn test_problem2() {
-        let input = read_example(4, 1);
+        let input = read_example(4, 1).unwrap();
         let cards: Vec<Card> = input
             .lines()
             .map(|line| Card::from_str(line).unwrap())
             .collect();
-        let q = queue_executor(&cards);
+        let q = queue_executor(&cards).unwrap();
         assert_eq!(q, 30);
     }
+
+    #[test]
+    fn test_solution() {
+        let input = read_example(4, 1).unwrap();
+        let answers = Day04.solve(&input, &Part::ALL).unwrap();
+        assert_eq!(answers.part1.as_deref(), Some("13"));
+        assert_eq!(answers.part2.as_deref(), Some("30"));
+    }
+
+    #[test]
+    fn test_queue_executor_out_of_range() {
+        let cards: Vec<Card> = ["Card 1: 1 2 | 1 2", "Card 2: 3 | 4"]
+            .into_iter()
+            .map(|line| Card::from_str(line).unwrap())
+            .collect();
+        assert!(queue_executor(&cards).is_err());
+    }
 }
@@ -1,63 +1,219 @@
-use std::str::FromStr;
+use std::{collections::HashMap, ops::Range, str::FromStr};
 
-use anyhow::anyhow;
+use anyhow::{anyhow, bail, Context};
 
-use crate::util::read_input;
+use crate::solution::Solution;
 
-pub fn day05() {
-    problem1();
-    problem2();
-}
+pub struct Day05;
 
-fn problem1() {
-    let input = read_input(5, 1);
-    let sm = SeedMapper::from_str(&input).unwrap();
-    let nearest_location = sm
-        .final_locations()
-        .iter()
-        .fold(u64::MAX, |acc, v| acc.min(*v));
-    println!("Nearest Location: {nearest_location}");
-}
+impl Solution for Day05 {
+    const DAY: usize = 5;
+    const EXAMPLE_ANSWERS: [Option<&'static str>; 2] = [Some("35"), Some("46")];
+
+    type Input = SeedMapper;
+    type Answer1 = u64;
+    type Answer2 = u64;
 
-fn problem2() {
-    let input = read_input(5, 1);
-    let sm = SeedMapper::from_str(&input).unwrap();
-    let nearest_locations = sm
-        .final_locations_ranges()
-        .iter()
-        .fold(u64::MAX, |acc, v| acc.min(*v));
-    println!("Nearest location: {nearest_locations}");
+    fn parse(&self, input: &str) -> anyhow::Result<Self::Input> {
+        SeedMapper::from_str(input)
+    }
+
+    fn part1(&self, sm: &SeedMapper) -> anyhow::Result<u64> {
+        sm.final_locations()
+            .into_iter()
+            .min()
+            .ok_or(anyhow!("No seeds"))
+    }
+
+    fn part2(&self, sm: &SeedMapper) -> anyhow::Result<u64> {
+        sm.final_locations_ranges()?
+            .iter()
+            .map(|r| r.start)
+            .min()
+            .ok_or(anyhow!("No seed ranges"))
+    }
 }
 
 #[derive(Debug, Clone)]
-struct SeedMapper {
+pub struct SeedMapper {
     seeds: Vec<u64>,
     maps: Vec<SeedMap>,
 }
 
 impl SeedMapper {
-    fn final_locations(&self) -> Vec<u64> {
-        self.maps.iter().fold(self.seeds.clone(), |acc, sm| {
-            let mut vals = Vec::new();
-            for start in acc {
-                vals.push(sm.destination(start));
+    /// Orders `maps` into a chain starting at `seed`, failing unless every map is on it and no
+    /// category appears twice.
+    pub fn new(seeds: Vec<u64>, maps: Vec<SeedMap>) -> anyhow::Result<SeedMapper> {
+        let mut by_source: HashMap<String, SeedMap> = HashMap::new();
+        for map in maps {
+            if let Some(existing) = by_source.get(&map.source) {
+                bail!(
+                    "Category `{}` is mapped twice: `{}` and `{}`",
+                    map.source,
+                    existing.name(),
+                    map.name()
+                );
+            }
+            by_source.insert(map.source.clone(), map);
+        }
+
+        let mut chain: Vec<SeedMap> = Vec::new();
+        let mut category = "seed".to_string();
+        while let Some(map) = by_source.remove(&category) {
+            let seen =
+                map.destination == "seed" || chain.iter().any(|m| m.destination == map.destination);
+            if seen {
+                bail!("Map `{}` leads back to `{}`", map.name(), map.destination);
             }
-            vals
+            category = map.destination.clone();
+            chain.push(map);
+        }
+        if !by_source.is_empty() {
+            let mut orphans: Vec<String> = by_source.values().map(SeedMap::name).collect();
+            orphans.sort();
+            bail!(
+                "Maps not connected to the seed chain: {}",
+                orphans.join(", ")
+            );
+        }
+        Ok(SeedMapper { seeds, maps: chain })
+    }
+
+    /// The categories along the chain, starting with `seed`.
+    pub fn categories(&self) -> Vec<&str> {
+        std::iter::once("seed")
+            .chain(self.maps.iter().map(|m| m.destination.as_str()))
+            .collect()
+    }
+
+    /// Indices of the maps that lead from category `from` to category `to`.
+    pub fn route(&self, from: &str, to: &str) -> anyhow::Result<Range<usize>> {
+        let categories = self.categories();
+        let position = |name: &str| {
+            categories
+                .iter()
+                .position(|c| *c == name)
+                .ok_or_else(|| anyhow!("Unknown category `{name}`"))
+        };
+        let (start, end) = (position(from)?, position(to)?);
+        if start > end {
+            bail!("No route from `{from}` to `{to}`: `{to}` comes first");
+        }
+        Ok(start..end)
+    }
+
+    /// Maps `value` from category `from` to category `to`.
+    pub fn map_value(&self, value: u64, from: &str, to: &str) -> anyhow::Result<u64> {
+        let route = self.route(from, to)?;
+        Ok(self.maps[route]
+            .iter()
+            .fold(value, |acc, sm| sm.destination(acc)))
+    }
+
+    /// Composes the maps from category `from` to category `to` into a single map.
+    pub fn compose_route(&self, from: &str, to: &str) -> anyhow::Result<PiecewiseMap> {
+        self.compose(self.route(from, to)?)
+    }
+
+    fn final_locations(&self) -> Vec<u64> {
+        let seed_to_location = self.composed();
+        self.seeds
+            .iter()
+            .map(|seed| seed_to_location.get(*seed))
+            .collect()
+    }
+
+    /// Composes the whole chain of maps into a single seed → location map.
+    pub fn composed(&self) -> PiecewiseMap {
+        self.maps.iter().fold(PiecewiseMap::identity(), |acc, sm| {
+            acc.then(&sm.piecewise())
         })
     }
 
-    fn final_locations_ranges(&self) -> Vec<u64> {
-        let mut destinations = Vec::new();
-        for seed_range in self.seeds.chunks(2) {
-            let start = seed_range[0];
-            let end = seed_range[1] + start;
-            for seed in start..end {
-                for seed_map in &self.maps {
-                    destinations.push(seed_map.destination(seed));
+    /// Composes the maps at `indices` (in almanac order) into a single map.
+    pub fn compose(&self, indices: Range<usize>) -> anyhow::Result<PiecewiseMap> {
+        let maps = self.maps.get(indices.clone()).ok_or_else(|| {
+            anyhow!(
+                "Map range {indices:?} out of bounds for {} maps",
+                self.maps.len()
+            )
+        })?;
+        Ok(maps.iter().fold(PiecewiseMap::identity(), |acc, sm| {
+            acc.then(&sm.piecewise())
+        }))
+    }
+
+    /// Every seed that ends up at `location`, in ascending order.
+    pub fn inverse(&self, location: u64) -> Vec<u64> {
+        let mut seeds = self.maps.iter().rev().fold(vec![location], |acc, sm| {
+            acc.into_iter().flat_map(|v| sm.inverse(v)).collect()
+        });
+        seeds.sort();
+        seeds.dedup();
+        seeds
+    }
+
+    /// Every seed range that ends up inside `locations`.
+    pub fn inverse_ranges(&self, locations: Range<u64>) -> Vec<Range<u64>> {
+        self.maps
+            .iter()
+            .rev()
+            .fold(vec![locations], |acc, sm| sm.inverse_ranges(&acc))
+    }
+
+    /// Walks location ranges in ascending order, paired with the seed ranges that reach them.
+    pub fn locations_ascending(&self) -> Vec<(Range<u64>, Range<u64>)> {
+        let mut pieces: Vec<(Range<u64>, Range<u64>)> = self
+            .composed()
+            .pieces()
+            .iter()
+            .map(|p| (p.apply(p.start)..p.apply(p.end), p.start..p.end))
+            .collect();
+        pieces.sort_by_key(|(locations, _)| locations.start);
+        pieces
+    }
+
+    /// Finds the nearest location for the seed ranges by searching backwards from location 0.
+    pub fn nearest_location_reverse(&self) -> anyhow::Result<Option<u64>> {
+        let seed_ranges = self.seed_ranges()?;
+        let mut nearest: Option<u64> = None;
+        for (locations, seeds) in self.locations_ascending() {
+            if nearest.is_some_and(|n| n <= locations.start) {
+                break;
+            }
+            for range in &seed_ranges {
+                let start = range.start.max(seeds.start);
+                if start < range.end.min(seeds.end) {
+                    let location = locations.start + (start - seeds.start);
+                    nearest = Some(nearest.map_or(location, |n| n.min(location)));
                 }
             }
         }
-        destinations
+        Ok(nearest)
+    }
+
+    /// Interprets the seeds as `start length` pairs describing `[start, start + length)`.
+    fn seed_ranges(&self) -> anyhow::Result<Vec<Range<u64>>> {
+        if !self.seeds.len().is_multiple_of(2) {
+            bail!(
+                "Seed ranges must come in pairs, got {} numbers",
+                self.seeds.len()
+            );
+        }
+        Ok(self
+            .seeds
+            .chunks(2)
+            .map(|pair| pair[0]..pair[0] + pair[1])
+            .collect())
+    }
+
+    /// Pushes every seed range through the whole chain of maps, returning the location ranges.
+    fn final_locations_ranges(&self) -> anyhow::Result<Vec<Range<u64>>> {
+        let seeds = self.seed_ranges()?;
+        Ok(self
+            .maps
+            .iter()
+            .fold(seeds, |acc, sm| sm.destination_ranges(&acc)))
     }
 }
 
@@ -65,64 +221,299 @@ impl FromStr for SeedMapper {
     type Err = anyhow::Error;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let mut lines = s.lines().filter(|l| !l.trim().is_empty());
-        let seeds: Vec<u64> = lines
-            .next()
-            .ok_or(anyhow!("Missing seeds"))?
+        let mut lines = s
+            .lines()
+            .enumerate()
+            .map(|(i, line)| (i + 1, line.trim()))
+            .filter(|(_, line)| !line.is_empty());
+        let (n, line) = lines.next().ok_or(anyhow!("Missing seeds"))?;
+        let seeds: Vec<u64> = line
             .split_once(':')
-            .ok_or(anyhow!("Unparseable seeds"))?
-            .1
-            .trim()
-            .split(' ')
-            .map(|n| u64::from_str(n))
-            .collect::<Result<Vec<_>, _>>()?;
+            .ok_or(anyhow!("Unparseable seeds"))
+            .and_then(|(_, seeds)| {
+                seeds
+                    .split_whitespace()
+                    .map(|seed| {
+                        u64::from_str(seed).with_context(|| format!("Invalid seed `{seed}`"))
+                    })
+                    .collect()
+            })
+            .with_context(|| format!("line {n}: {line:?}"))?;
 
-        let mut maps = Vec::new();
-        let mut map_lines: Vec<SeedMapLine> = Vec::new();
-        for line in lines {
-            let line = line.trim();
+        // Each map is built once all of its lines have been read.
+        let mut blocks: Vec<(usize, &str, Vec<SeedMapLine>)> = Vec::new();
+        for (n, line) in lines {
             if line.ends_with(':') {
-                maps.push(SeedMap(std::mem::take(&mut map_lines)));
+                blocks.push((n, line, Vec::new()));
                 continue;
             }
-            map_lines.push(SeedMapLine::from_str(line)?);
+            let (_, _, block) = blocks
+                .last_mut()
+                .ok_or_else(|| anyhow!("line {n}: map line {line:?} before any map header"))?;
+            block.push(SeedMapLine::from_str(line).with_context(|| format!("line {n}: {line:?}"))?);
         }
-        map_lines.sort_by_key(|k| k.start);
-        maps.push(SeedMap(std::mem::take(&mut map_lines)));
-        Ok(SeedMapper { seeds, maps })
+        let maps = blocks
+            .into_iter()
+            .map(|(n, header, lines)| {
+                SeedMap::new(header, lines).with_context(|| format!("line {n}: {header:?}"))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        SeedMapper::new(seeds, maps)
     }
 }
 
 #[derive(Debug, Clone)]
-struct SeedMap(Vec<SeedMapLine>);
+pub struct SeedMap {
+    source: String,
+    destination: String,
+    lines: Vec<SeedMapLine>,
+}
 
 impl SeedMap {
-    fn destination(&self, start: u64) -> u64 {
-        self.0
+    /// A map from its `<source>-to-<destination> map:` header and its lines, none of whose
+    /// source ranges may overlap.
+    pub fn new(header: &str, lines: Vec<SeedMapLine>) -> anyhow::Result<SeedMap> {
+        let (source, destination) = header
+            .strip_suffix(" map:")
+            .and_then(|h| h.split_once("-to-"))
+            .ok_or_else(|| anyhow!("Invalid map header `{header}`"))?;
+
+        // Overlaps would need a precedence rule, and the piecewise, range and inverse
+        // operations assume each value is covered by at most one line.
+        let mut sorted: Vec<&SeedMapLine> = lines.iter().collect();
+        sorted.sort_by_key(|l| l.start);
+        for pair in sorted.windows(2) {
+            let (a, b) = (pair[0], pair[1]);
+            if a.start + a.range > b.start {
+                bail!(
+                    "Overlapping source ranges in `{header}`: {}..{} and {}..{}",
+                    a.start,
+                    a.start + a.range,
+                    b.start,
+                    b.start + b.range
+                );
+            }
+        }
+
+        Ok(SeedMap {
+            source: source.to_string(),
+            destination: destination.to_string(),
+            lines,
+        })
+    }
+
+    pub fn name(&self) -> String {
+        format!("{}-to-{}", self.source, self.destination)
+    }
+
+    pub fn destination(&self, start: u64) -> u64 {
+        self.lines
             .iter()
             .find_map(|l| l.destination(start))
             .unwrap_or(start)
     }
+
+    /// Every source value that maps to `destination`, including itself if no line covers it.
+    pub fn inverse(&self, destination: u64) -> Vec<u64> {
+        let mut sources: Vec<u64> = self
+            .lines
+            .iter()
+            .filter_map(|l| l.inverse(destination))
+            .collect();
+        if !self
+            .lines
+            .iter()
+            .any(|l| l.destination(destination).is_some())
+        {
+            sources.push(destination);
+        }
+        sources
+    }
+
+    /// Every source range that maps into `ranges`.
+    pub fn inverse_ranges(&self, ranges: &[Range<u64>]) -> Vec<Range<u64>> {
+        let mut lines = self.lines.clone();
+        lines.sort_by_key(|l| l.start);
+
+        let mut sources = Vec::new();
+        for range in ranges {
+            sources.extend(lines.iter().filter_map(|l| l.inverse_range(range.clone())));
+
+            // Values outside every line's source map to themselves.
+            let mut cursor = range.start;
+            for line in &lines {
+                let end = line.start.min(range.end);
+                if cursor < end {
+                    sources.push(cursor..end);
+                }
+                cursor = cursor.max(line.start + line.range);
+            }
+            if cursor < range.end {
+                sources.push(cursor..range.end);
+            }
+        }
+        sources
+    }
+
+    /// Converts the lines into a piecewise map covering every value, filling gaps with identity.
+    pub fn piecewise(&self) -> PiecewiseMap {
+        let mut lines = self.lines.clone();
+        lines.sort_by_key(|l| l.start);
+
+        let mut pieces = Vec::new();
+        let mut cursor = 0;
+        for line in lines {
+            let start = line.start.max(cursor);
+            let end = line.start + line.range;
+            if start >= end {
+                continue;
+            }
+            if start > cursor {
+                pieces.push(Piece::new(cursor, start, 0));
+            }
+            pieces.push(Piece::new(
+                start,
+                end,
+                i128::from(line.destination) - i128::from(line.start),
+            ));
+            cursor = end;
+        }
+        if cursor < u64::MAX {
+            pieces.push(Piece::new(cursor, u64::MAX, 0));
+        }
+        PiecewiseMap::from_pieces(pieces)
+    }
+
+    /// Maps each range through this map, splitting wherever it crosses a line boundary.
+    /// Parts of a range not covered by any line map to themselves.
+    fn destination_ranges(&self, ranges: &[Range<u64>]) -> Vec<Range<u64>> {
+        let mut mapped = Vec::new();
+        for range in ranges {
+            let mut pending = vec![range.clone()];
+            for line in &self.lines {
+                let mut unmatched = Vec::new();
+                for r in pending {
+                    let (hit, rest) = line.destination_range(r);
+                    mapped.extend(hit);
+                    unmatched.extend(rest);
+                }
+                pending = unmatched;
+            }
+            mapped.extend(pending);
+        }
+        mapped
+    }
 }
 
 impl FromStr for SeedMap {
     type Err = anyhow::Error;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let sms = s
-            .lines()
-            .map(|l| SeedMapLine::from_str(l))
+        let mut lines = s.lines();
+        let header = lines.next().ok_or(anyhow!("Missing map header"))?.trim();
+        let lines = lines
+            .enumerate()
+            .map(|(i, line)| {
+                SeedMapLine::from_str(line).with_context(|| format!("line {}: {line:?}", i + 2))
+            })
             .collect::<Result<Vec<_>, _>>()?;
-        Ok(SeedMap(sms))
+        SeedMap::new(header, lines)
+    }
+}
+
+/// A contiguous range `[start, end)` that is shifted by `offset`. Offsets can span the whole
+/// `u64` range in either direction, so they're kept as `i128`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Piece {
+    start: u64,
+    end: u64,
+    offset: i128,
+}
+
+impl Piece {
+    fn new(start: u64, end: u64, offset: i128) -> Piece {
+        Piece { start, end, offset }
+    }
+
+    fn apply(&self, value: u64) -> u64 {
+        shift(value, self.offset)
+    }
+}
+
+/// Adds `offset` to `value`, saturating at the ends of the `u64` range.
+fn shift(value: u64, offset: i128) -> u64 {
+    (i128::from(value) + offset).clamp(0, i128::from(u64::MAX)) as u64
+}
+
+/// A sorted, gap-free sequence of [`Piece`]s covering `[0, u64::MAX)`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PiecewiseMap {
+    pieces: Vec<Piece>,
+}
+
+impl PiecewiseMap {
+    pub fn identity() -> PiecewiseMap {
+        PiecewiseMap {
+            pieces: vec![Piece::new(0, u64::MAX, 0)],
+        }
+    }
+
+    /// Builds a map from sorted, contiguous pieces, merging neighbours that share an offset.
+    fn from_pieces(pieces: Vec<Piece>) -> PiecewiseMap {
+        let mut merged: Vec<Piece> = Vec::with_capacity(pieces.len());
+        for piece in pieces {
+            match merged.last_mut() {
+                Some(last) if last.end == piece.start && last.offset == piece.offset => {
+                    last.end = piece.end
+                }
+                _ => merged.push(piece),
+            }
+        }
+        PiecewiseMap { pieces: merged }
+    }
+
+    pub fn pieces(&self) -> &[Piece] {
+        &self.pieces
+    }
+
+    /// Looks up `value` with a binary search over the pieces.
+    pub fn get(&self, value: u64) -> u64 {
+        let i = self.pieces.partition_point(|p| p.end <= value);
+        match self.pieces.get(i) {
+            Some(piece) if piece.start <= value => piece.apply(value),
+            _ => value,
+        }
+    }
+
+    /// Returns the map equivalent to applying `self` and then `next`.
+    pub fn then(&self, next: &PiecewiseMap) -> PiecewiseMap {
+        let mut pieces = Vec::new();
+        for piece in &self.pieces {
+            let image_start = piece.apply(piece.start);
+            let image_end = piece.apply(piece.end);
+            let first = next.pieces.partition_point(|p| p.end <= image_start);
+            for other in next.pieces[first..]
+                .iter()
+                .take_while(|p| p.start < image_end)
+            {
+                let start = other.start.max(image_start);
+                let end = other.end.min(image_end);
+                pieces.push(Piece::new(
+                    shift(start, -piece.offset),
+                    shift(end, -piece.offset),
+                    piece.offset + other.offset,
+                ));
+            }
+        }
+        PiecewiseMap::from_pieces(pieces)
     }
 }
 
 #[derive(Debug, Clone, Copy)]
-struct SeedMapLine {
+pub struct SeedMapLine {
     destination: u64,
     start: u64,
     range: u64,
-    counter: u64,
 }
 
 impl SeedMapLine {
@@ -131,24 +522,57 @@ impl SeedMapLine {
             destination,
             start,
             range,
-            counter: 0,
         }
     }
 
-    fn destination(&self, start: u64) -> Option<u64> {
+    pub fn destination(&self, start: u64) -> Option<u64> {
         if start < self.start || start >= self.start + self.range {
             return None;
         }
         let diff = start - self.start;
         Some(self.destination + diff)
     }
+
+    /// The source value that this line maps to `destination`, if any.
+    pub fn inverse(&self, destination: u64) -> Option<u64> {
+        if destination < self.destination || destination >= self.destination + self.range {
+            return None;
+        }
+        Some(self.start + (destination - self.destination))
+    }
+
+    /// The source range that this line maps into `range`, if any.
+    pub fn inverse_range(&self, range: Range<u64>) -> Option<Range<u64>> {
+        let start = range.start.max(self.destination);
+        let end = range.end.min(self.destination + self.range);
+        if start >= end {
+            return None;
+        }
+        Some((self.start + start - self.destination)..(self.start + end - self.destination))
+    }
+
+    /// Splits `range` into the part this line maps (already translated) and the parts it doesn't.
+    fn destination_range(&self, range: Range<u64>) -> (Option<Range<u64>>, Vec<Range<u64>>) {
+        let start = range.start.max(self.start);
+        let end = range.end.min(self.start + self.range);
+        if start >= end {
+            return (None, vec![range]);
+        }
+
+        let mapped = (self.destination + start - self.start)..(self.destination + end - self.start);
+        let rest = [range.start..start, end..range.end]
+            .into_iter()
+            .filter(|r| !r.is_empty())
+            .collect();
+        (Some(mapped), rest)
+    }
 }
 
 impl FromStr for SeedMapLine {
     type Err = anyhow::Error;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let mut split = s.split(' ');
+        let mut split = s.split_whitespace();
         let destination = split
             .next()
             .ok_or(anyhow!("Missing destination"))?
@@ -161,7 +585,10 @@ impl FromStr for SeedMapLine {
 
 #[cfg(test)]
 mod tests {
-    use crate::util::read_example;
+    use crate::{
+        solution::{DynSolution, Part},
+        util::read_example,
+    };
 
     use super::*;
 
@@ -170,18 +597,170 @@ mod tests {
         let sm = SeedMapLine::from_str("50 98 2").unwrap();
         assert_eq!(sm.destination(98), Some(50));
         assert_eq!(sm.destination(10), None);
+
+        assert!(SeedMap::from_str("seed-to-soil map:\n50 98 2\n10 100 5").is_ok());
+        let err = SeedMap::from_str("seed-to-soil map:\n50 98 2\n10 99 5").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Overlapping source ranges in `seed-to-soil map:`: 98..100 and 99..104"
+        );
     }
 
     #[test]
     fn test_problem1() {
-        let input = read_example(5, 1);
+        let input = read_example(5, 1).unwrap();
         let mapper = SeedMapper::from_str(&input).unwrap();
         let min = mapper.final_locations();
         assert_eq!(min, &[82, 43, 86, 35]);
     }
 
+    #[test]
+    #[allow(clippy::single_range_in_vec_init)]
+    fn test_destination_range() {
+        let sm = SeedMapLine::from_str("52 50 48").unwrap();
+        assert_eq!(sm.destination_range(79..93), (Some(81..95), vec![]));
+        assert_eq!(sm.destination_range(10..20), (None, vec![10..20]));
+        assert_eq!(
+            sm.destination_range(40..100),
+            (Some(52..100), vec![40..50, 98..100])
+        );
+    }
+
+    #[test]
+    fn test_composed() {
+        let input = read_example(5, 1).unwrap();
+        let mapper = SeedMapper::from_str(&input).unwrap();
+        let composed = mapper.composed();
+        let split = mapper
+            .compose(0..4)
+            .unwrap()
+            .then(&mapper.compose(4..mapper.maps.len()).unwrap());
+        assert_eq!(composed, split);
+
+        for seed in 0..120 {
+            let expected = mapper.maps.iter().fold(seed, |v, sm| sm.destination(v));
+            assert_eq!(composed.get(seed), expected, "seed {seed}");
+        }
+        assert!(mapper.compose(0..20).is_err());
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        let error = |input: &str| format!("{:#}", SeedMapper::from_str(input).unwrap_err());
+        assert_eq!(
+            error("seeds: 79 14\n\nseed-to-soil map:\n50 98 2\n52 x 48\n"),
+            "line 5: \"52 x 48\": invalid digit found in string"
+        );
+        assert_eq!(
+            error("seeds: 79 1x4\n"),
+            "line 1: \"seeds: 79 1x4\": Invalid seed `1x4`: invalid digit found in string"
+        );
+        assert_eq!(
+            error("seeds: 1\n\nseed-to-soil map:\n5 1 1\n1 1 1\n"),
+            "line 3: \"seed-to-soil map:\": Overlapping source ranges in `seed-to-soil map:`: \
+             1..2 and 1..2"
+        );
+        let mapper = SeedMapper::from_str("seeds: 79  14\n").unwrap();
+        assert_eq!(mapper.seeds, [79, 14]);
+    }
+
+    #[test]
+    fn test_large_values() {
+        let input = "seeds: 0 10\n\nseed-to-soil map:\n18446744073709551000 0 100\n";
+        let mapper = SeedMapper::from_str(input).unwrap();
+        let composed = mapper.composed();
+        for seed in [0, 5, 99, 100, u64::MAX - 1] {
+            let expected = mapper.maps.iter().fold(seed, |v, sm| sm.destination(v));
+            assert_eq!(composed.get(seed), expected, "seed {seed}");
+        }
+        assert_eq!(Day05.part1(&mapper).unwrap(), 18446744073709551000);
+        assert_eq!(Day05.part2(&mapper).unwrap(), 18446744073709551000);
+        assert_eq!(
+            mapper.map_value(0, "seed", "soil").unwrap(),
+            18446744073709551000
+        );
+    }
+
+    #[test]
+    fn test_categories() {
+        let input = read_example(5, 1).unwrap();
+        let mapper = SeedMapper::from_str(&input).unwrap();
+        assert_eq!(
+            mapper.categories(),
+            [
+                "seed",
+                "soil",
+                "fertilizer",
+                "water",
+                "light",
+                "temperature",
+                "humidity",
+                "location"
+            ]
+        );
+        assert_eq!(mapper.map_value(79, "seed", "soil").unwrap(), 81);
+        assert_eq!(mapper.map_value(81, "soil", "humidity").unwrap(), 78);
+        assert_eq!(mapper.map_value(79, "seed", "location").unwrap(), 82);
+        assert_eq!(mapper.map_value(74, "light", "light").unwrap(), 74);
+        assert_eq!(
+            mapper.compose_route("soil", "humidity").unwrap().get(81),
+            78
+        );
+        assert!(mapper.map_value(78, "humidity", "soil").is_err());
+        assert!(mapper.map_value(1, "seed", "moon").is_err());
+
+        let shuffled = "seeds: 1\n\nsoil-to-water map:\n5 1 1\n\nseed-to-soil map:\n1 2 3\n";
+        let mapper = SeedMapper::from_str(shuffled).unwrap();
+        assert_eq!(mapper.categories(), ["seed", "soil", "water"]);
+
+        let cycle = "seeds: 1\n\nseed-to-soil map:\n1 2 3\n\nsoil-to-seed map:\n5 1 1\n";
+        let err = SeedMapper::from_str(cycle).unwrap_err();
+        assert_eq!(err.to_string(), "Map `soil-to-seed` leads back to `seed`");
+
+        let broken = "seeds: 1\n\nseed-to-soil map:\n1 2 3\n\nwater-to-light map:\n5 1 1\n";
+        let err = SeedMapper::from_str(broken).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Maps not connected to the seed chain: water-to-light"
+        );
+    }
+
+    #[test]
+    fn test_inverse() {
+        let line = SeedMapLine::from_str("52 50 48").unwrap();
+        assert_eq!(line.inverse(81), Some(79));
+        assert_eq!(line.inverse(10), None);
+        assert_eq!(line.inverse_range(40..60), Some(50..58));
+
+        let input = read_example(5, 1).unwrap();
+        let mapper = SeedMapper::from_str(&input).unwrap();
+        for (seed, location) in [(79, 82), (14, 43), (55, 86), (13, 35)] {
+            assert!(mapper.inverse(location).contains(&seed));
+        }
+        for location in 0..120 {
+            for seed in mapper.inverse(location) {
+                assert_eq!(mapper.composed().get(seed), location);
+            }
+        }
+
+        let seeds = mapper.inverse_ranges(46..47);
+        assert!(seeds.iter().any(|r| r.contains(&82)));
+        assert_eq!(mapper.nearest_location_reverse().unwrap(), Some(46));
+    }
+
     #[test]
     fn test_problem2() {
-        // let input = read_example(5, 1);
+        let input = read_example(5, 1).unwrap();
+        let mapper = SeedMapper::from_str(&input).unwrap();
+        assert_eq!(Day05.part2(&mapper).unwrap(), 46);
+    }
+
+    #[test]
+    fn test_example_answers() {
+        let input = read_example(5, 1).unwrap();
+        let answers = Day05.solve(&input, &Part::ALL).unwrap();
+        for part in Part::ALL {
+            assert_eq!(answers.get(part), Day05.example_answer(part), "part {part}");
+        }
     }
 }
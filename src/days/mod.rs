@@ -1,3 +1,5 @@
+use crate::solution::DynSolution;
+
 mod day01;
 mod day02;
 mod day03;
@@ -9,3 +11,29 @@ pub use day02::*;
 pub use day03::*;
 pub use day04::*;
 pub use day05::*;
+
+/// Puzzle rules that can be varied from the command line. The default is the puzzle as written.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Config {
+    /// Day 2's bag of cubes, replacing [`PUZZLE_BAG`].
+    pub bag: Option<Handful>,
+}
+
+/// Every implemented day, in order.
+pub fn registry(config: &Config) -> Vec<Box<dyn DynSolution>> {
+    let day02 = match &config.bag {
+        Some(bag) => Day02::new(bag.clone(), Color::PUZZLE.to_vec()),
+        None => Day02::default(),
+    };
+    vec![
+        Box::new(Day01),
+        Box::new(day02),
+        Box::new(Day03),
+        Box::new(Day04),
+        Box::new(Day05),
+    ]
+}
+
+pub fn find(day: usize, config: &Config) -> Option<Box<dyn DynSolution>> {
+    registry(config).into_iter().find(|s| s.day() == day)
+}
@@ -0,0 +1,176 @@
+use std::str::FromStr;
+
+use anyhow::bail;
+
+use crate::point::Point;
+
+/// One of the eight compass directions on a grid where `y` grows downwards.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Dir {
+    Up,
+    Down,
+    Left,
+    Right,
+    TopLeft,
+    TopRight,
+    BottomLeft,
+    BottomRight,
+}
+
+impl Dir {
+    /// Every direction, clockwise from `Up`.
+    pub const ALL: [Dir; 8] = [
+        Dir::Up,
+        Dir::TopRight,
+        Dir::Right,
+        Dir::BottomRight,
+        Dir::Down,
+        Dir::BottomLeft,
+        Dir::Left,
+        Dir::TopLeft,
+    ];
+
+    /// The four orthogonal directions, clockwise from `Up`.
+    pub const CARDINAL: [Dir; 4] = [Dir::Up, Dir::Right, Dir::Down, Dir::Left];
+
+    /// The four diagonal directions, clockwise from `TopRight`.
+    pub const DIAGONAL: [Dir; 4] = [
+        Dir::TopRight,
+        Dir::BottomRight,
+        Dir::BottomLeft,
+        Dir::TopLeft,
+    ];
+
+    fn index(self) -> usize {
+        Dir::ALL.iter().position(|d| *d == self).unwrap()
+    }
+
+    /// Rotates clockwise by `steps` eighths of a turn (negative is counter-clockwise).
+    pub fn rotate(self, steps: i32) -> Dir {
+        Dir::ALL[(self.index() as i32 + steps).rem_euclid(8) as usize]
+    }
+
+    pub fn turn_right_45(self) -> Dir {
+        self.rotate(1)
+    }
+
+    pub fn turn_left_45(self) -> Dir {
+        self.rotate(-1)
+    }
+
+    pub fn turn_right(self) -> Dir {
+        self.rotate(2)
+    }
+
+    pub fn turn_left(self) -> Dir {
+        self.rotate(-2)
+    }
+
+    pub fn opposite(self) -> Dir {
+        self.rotate(4)
+    }
+
+    pub fn is_cardinal(self) -> bool {
+        matches!(self, Dir::Up | Dir::Down | Dir::Left | Dir::Right)
+    }
+
+    pub fn is_diagonal(self) -> bool {
+        !self.is_cardinal()
+    }
+
+    /// The unit step in this direction.
+    pub fn offset(self) -> Point {
+        match self {
+            Dir::Up => Point::new(0, -1),
+            Dir::Down => Point::new(0, 1),
+            Dir::Left => Point::new(-1, 0),
+            Dir::Right => Point::new(1, 0),
+            Dir::TopLeft => Point::new(-1, -1),
+            Dir::TopRight => Point::new(1, -1),
+            Dir::BottomLeft => Point::new(-1, 1),
+            Dir::BottomRight => Point::new(1, 1),
+        }
+    }
+
+    /// Parses `U/D/L/R`, compass `N/S/E/W` or an arrow such as `^`, `>` or `↘`.
+    pub fn from_char(c: char) -> anyhow::Result<Dir> {
+        Ok(match c {
+            'U' | 'N' | '^' | '↑' => Dir::Up,
+            'D' | 'S' | 'v' | '↓' => Dir::Down,
+            'L' | 'W' | '<' | '←' => Dir::Left,
+            'R' | 'E' | '>' | '→' => Dir::Right,
+            '↖' => Dir::TopLeft,
+            '↗' => Dir::TopRight,
+            '↙' => Dir::BottomLeft,
+            '↘' => Dir::BottomRight,
+            _ => bail!("Invalid direction `{c}`"),
+        })
+    }
+}
+
+impl FromStr for Dir {
+    type Err = anyhow::Error;
+
+    /// Accepts a single direction character, or a two-letter compass point such as `NE`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut chars = s.chars();
+        match (chars.next(), chars.next(), chars.next()) {
+            (Some(c), None, None) => Dir::from_char(c),
+            (Some(a), Some(b), None) => Ok(match (a, b) {
+                ('N', 'W') => Dir::TopLeft,
+                ('N', 'E') => Dir::TopRight,
+                ('S', 'W') => Dir::BottomLeft,
+                ('S', 'E') => Dir::BottomRight,
+                _ => bail!("Invalid direction `{s}`"),
+            }),
+            _ => bail!("Invalid direction `{s}`"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_turns() {
+        assert_eq!(Dir::Up.turn_right(), Dir::Right);
+        assert_eq!(Dir::Up.turn_left(), Dir::Left);
+        assert_eq!(Dir::Up.turn_right_45(), Dir::TopRight);
+        assert_eq!(Dir::Up.turn_left_45(), Dir::TopLeft);
+        assert_eq!(Dir::BottomLeft.opposite(), Dir::TopRight);
+        for dir in Dir::ALL {
+            assert_eq!(dir.turn_left().turn_right(), dir);
+            assert_eq!(dir.offset() + dir.opposite().offset(), Point::ORIGIN);
+            assert_eq!(dir.rotate(8), dir);
+        }
+    }
+
+    #[test]
+    fn test_sets() {
+        assert!(Dir::CARDINAL.iter().all(|d| d.is_cardinal()));
+        assert!(Dir::DIAGONAL.iter().all(|d| d.is_diagonal()));
+        assert!(Dir::CARDINAL
+            .iter()
+            .all(|d| d.offset().manhattan(Point::ORIGIN) == 1));
+        assert!(Dir::DIAGONAL
+            .iter()
+            .all(|d| d.offset().manhattan(Point::ORIGIN) == 2));
+    }
+
+    #[test]
+    fn test_parse() {
+        for (s, dir) in [
+            ("U", Dir::Up),
+            ("S", Dir::Down),
+            ("<", Dir::Left),
+            ("→", Dir::Right),
+            ("↖", Dir::TopLeft),
+            ("NE", Dir::TopRight),
+        ] {
+            assert_eq!(s.parse::<Dir>().unwrap(), dir);
+        }
+        assert!("X".parse::<Dir>().is_err());
+        assert!("NN".parse::<Dir>().is_err());
+    }
+}
@@ -0,0 +1,501 @@
+use std::ops::{Index, IndexMut};
+
+use anyhow::bail;
+
+use crate::{dir::Dir, point::Point};
+
+/// A dense rectangular grid stored row-major, addressed by `(x, y)` with the origin top-left.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Grid<T> {
+    width: usize,
+    height: usize,
+    cells: Vec<T>,
+}
+
+impl<T> Grid<T> {
+    pub fn new(width: usize, height: usize, fill: T) -> Grid<T>
+    where
+        T: Clone,
+    {
+        Grid {
+            width,
+            height,
+            cells: vec![fill; width * height],
+        }
+    }
+
+    /// Builds a grid from row-major cells, failing if they don't fill `width` × `height`.
+    pub fn from_cells(width: usize, height: usize, cells: Vec<T>) -> anyhow::Result<Grid<T>> {
+        if cells.len() != width * height {
+            bail!("{} cells cannot fill a {width}x{height} grid", cells.len());
+        }
+        Ok(Grid {
+            width,
+            height,
+            cells,
+        })
+    }
+
+    /// Parses one row per line, mapping each character to a cell. Rows must all be the same width.
+    pub fn parse(s: &str, mut cell: impl FnMut(char) -> T) -> anyhow::Result<Grid<T>> {
+        let mut width = None;
+        let mut height = 0;
+        let mut cells = Vec::new();
+        for (y, line) in s.lines().enumerate() {
+            let before = cells.len();
+            cells.extend(line.chars().map(&mut cell));
+            let row_width = cells.len() - before;
+            match width {
+                None => width = Some(row_width),
+                Some(w) if w != row_width => {
+                    bail!("line {}: expected {w} cells, found {row_width}", y + 1)
+                }
+                _ => {}
+            }
+            height += 1;
+        }
+        Grid::from_cells(width.unwrap_or(0), height, cells)
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn in_bounds(&self, x: usize, y: usize) -> bool {
+        x < self.width && y < self.height
+    }
+
+    pub fn get(&self, x: usize, y: usize) -> Option<&T> {
+        if !self.in_bounds(x, y) {
+            return None;
+        }
+        self.cells.get(y * self.width + x)
+    }
+
+    pub fn get_mut(&mut self, x: usize, y: usize) -> Option<&mut T> {
+        if !self.in_bounds(x, y) {
+            return None;
+        }
+        self.cells.get_mut(y * self.width + x)
+    }
+
+    pub fn get_point(&self, p: Point) -> Option<&T> {
+        let (x, y) = p.to_index()?;
+        self.get(x, y)
+    }
+
+    pub fn get_point_mut(&mut self, p: Point) -> Option<&mut T> {
+        let (x, y) = p.to_index()?;
+        self.get_mut(x, y)
+    }
+
+    pub fn contains_point(&self, p: Point) -> bool {
+        p.to_index().is_some_and(|(x, y)| self.in_bounds(x, y))
+    }
+
+    /// Replaces the cell at `(x, y)`, returning the old value, or `None` if out of bounds.
+    pub fn set(&mut self, x: usize, y: usize, value: T) -> Option<T> {
+        self.get_mut(x, y)
+            .map(|cell| std::mem::replace(cell, value))
+    }
+
+    pub fn row(&self, y: usize) -> Option<&[T]> {
+        if y >= self.height {
+            return None;
+        }
+        Some(&self.cells[y * self.width..(y + 1) * self.width])
+    }
+
+    pub fn rows(&self) -> impl Iterator<Item = &[T]> {
+        (0..self.height).map(|y| &self.cells[y * self.width..(y + 1) * self.width])
+    }
+
+    pub fn column(&self, x: usize) -> impl Iterator<Item = &T> {
+        let width = self.width;
+        let cells = if x < width { &self.cells[x..] } else { &[] };
+        cells.iter().step_by(width.max(1))
+    }
+
+    pub fn columns(&self) -> impl Iterator<Item = impl Iterator<Item = &T>> {
+        (0..self.width).map(|x| self.column(x))
+    }
+
+    /// Every cell with its coordinates, in row-major order.
+    pub fn iter(&self) -> impl Iterator<Item = ((usize, usize), &T)> {
+        let width = self.width.max(1);
+        self.cells
+            .iter()
+            .enumerate()
+            .map(move |(i, cell)| ((i % width, i / width), cell))
+    }
+
+    /// In-bounds coordinates of the four orthogonal neighbours of `(x, y)`, clockwise from up.
+    pub fn neighbours4(&self, x: usize, y: usize) -> impl Iterator<Item = (usize, usize)> + '_ {
+        self.step_all(x, y, &Dir::CARDINAL)
+    }
+
+    /// In-bounds coordinates of all eight neighbours of `(x, y)`, clockwise from up.
+    pub fn neighbours8(&self, x: usize, y: usize) -> impl Iterator<Item = (usize, usize)> + '_ {
+        self.step_all(x, y, &Dir::ALL)
+    }
+
+    fn step_all<'a>(
+        &'a self,
+        x: usize,
+        y: usize,
+        dirs: &'static [Dir],
+    ) -> impl Iterator<Item = (usize, usize)> + 'a {
+        let here = Point::from((x, y));
+        dirs.iter().filter_map(move |dir| {
+            let (nx, ny) = (here + dir.offset()).to_index()?;
+            self.in_bounds(nx, ny).then_some((nx, ny))
+        })
+    }
+
+    /// Coordinates of the first cell (row-major) matching `predicate`.
+    pub fn find(&self, mut predicate: impl FnMut(&T) -> bool) -> Option<(usize, usize)> {
+        self.iter()
+            .find(|(_, cell)| predicate(cell))
+            .map(|(p, _)| p)
+    }
+
+    /// Coordinates of every cell matching `predicate`, in row-major order.
+    pub fn find_all<'a>(
+        &'a self,
+        mut predicate: impl FnMut(&T) -> bool + 'a,
+    ) -> impl Iterator<Item = (usize, usize)> + 'a {
+        self.iter()
+            .filter(move |(_, cell)| predicate(cell))
+            .map(|(p, _)| p)
+    }
+
+    /// Collects every cell reachable from `start` through cells matching `predicate`, stepping
+    /// only in `dirs`. Returns `None` if `start` itself doesn't match or is out of bounds.
+    pub fn flood_fill(
+        &self,
+        start: (usize, usize),
+        dirs: &[Dir],
+        mut predicate: impl FnMut(&T) -> bool,
+    ) -> Option<Region> {
+        let mut visited = Grid::new(self.width, self.height, false);
+        self.fill_from(start, dirs, &mut predicate, &mut visited)
+    }
+
+    /// Splits the cells matching `predicate` into connected regions, in row-major order of
+    /// their first cell.
+    pub fn regions(&self, dirs: &[Dir], mut predicate: impl FnMut(&T) -> bool) -> Vec<Region> {
+        let mut visited = Grid::new(self.width, self.height, false);
+        let mut regions = Vec::new();
+        for y in 0..self.height {
+            for x in 0..self.width {
+                if visited[(x, y)] {
+                    continue;
+                }
+                if let Some(region) = self.fill_from((x, y), dirs, &mut predicate, &mut visited) {
+                    regions.push(region);
+                }
+            }
+        }
+        regions
+    }
+
+    fn fill_from(
+        &self,
+        start: (usize, usize),
+        dirs: &[Dir],
+        predicate: &mut impl FnMut(&T) -> bool,
+        visited: &mut Grid<bool>,
+    ) -> Option<Region> {
+        if !self.get(start.0, start.1).is_some_and(&mut *predicate) {
+            return None;
+        }
+        visited[start] = true;
+        let mut cells = Vec::new();
+        let mut stack = vec![start];
+        while let Some(cell) = stack.pop() {
+            cells.push(cell);
+            for dir in dirs {
+                let Some(next) = (Point::from(cell) + dir.offset()).to_index() else {
+                    continue;
+                };
+                if visited.get(next.0, next.1) == Some(&false) && predicate(&self[next]) {
+                    visited[next] = true;
+                    stack.push(next);
+                }
+            }
+        }
+        Some(Region::new(cells))
+    }
+
+    /// In-bounds cells outside `region` that are one step in `dirs` from any of its cells.
+    pub fn region_neighbours(&self, region: &Region, dirs: &[Dir]) -> Vec<(usize, usize)> {
+        let mut neighbours: Vec<(usize, usize)> = region
+            .cells
+            .iter()
+            .flat_map(|cell| dirs.iter().map(|dir| Point::from(*cell) + dir.offset()))
+            .filter_map(Point::to_index)
+            .filter(|(x, y)| self.in_bounds(*x, *y) && !region.contains((*x, *y)))
+            .collect();
+        neighbours.sort_by_key(|(x, y)| (*y, *x));
+        neighbours.dedup();
+        neighbours
+    }
+
+    /// The cells from corner `min` to corner `max` inclusive, clamped to the grid.
+    pub fn crop(&self, min: (usize, usize), max: (usize, usize)) -> Grid<T>
+    where
+        T: Clone,
+    {
+        if self.cells.is_empty() || min.0 >= self.width || min.1 >= self.height {
+            return Grid::default();
+        }
+        let max = (max.0.min(self.width - 1), max.1.min(self.height - 1));
+        if max.0 < min.0 || max.1 < min.1 {
+            return Grid::default();
+        }
+        let width = max.0 - min.0 + 1;
+        let height = max.1 - min.1 + 1;
+        self.remap(width, height, |x, y| (min.0 + x, min.1 + y))
+    }
+
+    pub fn map<U>(&self, f: impl FnMut(&T) -> U) -> Grid<U> {
+        Grid {
+            width: self.width,
+            height: self.height,
+            cells: self.cells.iter().map(f).collect(),
+        }
+    }
+
+    /// Builds a `width` × `height` grid where each cell is copied from `source(x, y)` of `self`.
+    fn remap(
+        &self,
+        width: usize,
+        height: usize,
+        source: impl Fn(usize, usize) -> (usize, usize),
+    ) -> Grid<T>
+    where
+        T: Clone,
+    {
+        let mut cells = Vec::with_capacity(self.cells.len());
+        for y in 0..height {
+            for x in 0..width {
+                cells.push(self[source(x, y)].clone());
+            }
+        }
+        Grid {
+            width,
+            height,
+            cells,
+        }
+    }
+
+    pub fn transpose(&self) -> Grid<T>
+    where
+        T: Clone,
+    {
+        self.remap(self.height, self.width, |x, y| (y, x))
+    }
+
+    /// Rotates a quarter turn clockwise.
+    pub fn rotate_cw(&self) -> Grid<T>
+    where
+        T: Clone,
+    {
+        let h = self.height;
+        self.remap(self.height, self.width, |x, y| (y, h - 1 - x))
+    }
+
+    /// Rotates a quarter turn counter-clockwise.
+    pub fn rotate_ccw(&self) -> Grid<T>
+    where
+        T: Clone,
+    {
+        let w = self.width;
+        self.remap(self.height, self.width, |x, y| (w - 1 - y, x))
+    }
+
+    /// Mirrors left to right.
+    pub fn flip_horizontal(&self) -> Grid<T>
+    where
+        T: Clone,
+    {
+        let w = self.width;
+        self.remap(self.width, self.height, |x, y| (w - 1 - x, y))
+    }
+
+    /// Mirrors top to bottom.
+    pub fn flip_vertical(&self) -> Grid<T>
+    where
+        T: Clone,
+    {
+        let h = self.height;
+        self.remap(self.width, self.height, |x, y| (x, h - 1 - y))
+    }
+}
+
+/// A connected set of cells along with their inclusive bounding box.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Region {
+    /// Cells in row-major order.
+    pub cells: Vec<(usize, usize)>,
+    pub min: (usize, usize),
+    pub max: (usize, usize),
+}
+
+impl Region {
+    fn new(mut cells: Vec<(usize, usize)>) -> Region {
+        cells.sort_by_key(|(x, y)| (*y, *x));
+        let min = (
+            cells.iter().map(|c| c.0).min().unwrap_or(0),
+            cells.iter().map(|c| c.1).min().unwrap_or(0),
+        );
+        let max = (
+            cells.iter().map(|c| c.0).max().unwrap_or(0),
+            cells.iter().map(|c| c.1).max().unwrap_or(0),
+        );
+        Region { cells, min, max }
+    }
+
+    pub fn len(&self) -> usize {
+        self.cells.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.cells.is_empty()
+    }
+
+    pub fn contains(&self, cell: (usize, usize)) -> bool {
+        cell.0 >= self.min.0
+            && cell.0 <= self.max.0
+            && cell.1 >= self.min.1
+            && cell.1 <= self.max.1
+            && self
+                .cells
+                .binary_search_by_key(&(cell.1, cell.0), |(x, y)| (*y, *x))
+                .is_ok()
+    }
+}
+
+impl<T> Default for Grid<T> {
+    fn default() -> Self {
+        Grid {
+            width: 0,
+            height: 0,
+            cells: Vec::new(),
+        }
+    }
+}
+
+impl<T> Index<(usize, usize)> for Grid<T> {
+    type Output = T;
+
+    fn index(&self, (x, y): (usize, usize)) -> &T {
+        self.get(x, y).unwrap_or_else(|| {
+            panic!(
+                "({x}, {y}) is outside a {}x{} grid",
+                self.width, self.height
+            )
+        })
+    }
+}
+
+impl<T> IndexMut<(usize, usize)> for Grid<T> {
+    fn index_mut(&mut self, (x, y): (usize, usize)) -> &mut T {
+        let (width, height) = (self.width, self.height);
+        self.get_mut(x, y)
+            .unwrap_or_else(|| panic!("({x}, {y}) is outside a {width}x{height} grid"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample() -> Grid<char> {
+        Grid::parse("abc\ndef", |c| c).unwrap()
+    }
+
+    fn to_string(grid: &Grid<char>) -> String {
+        grid.rows()
+            .map(|row| row.iter().collect::<String>())
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    #[test]
+    fn test_parse_and_access() {
+        let mut grid = sample();
+        assert_eq!((grid.width(), grid.height()), (3, 2));
+        assert_eq!(grid.get(2, 1), Some(&'f'));
+        assert_eq!(grid.get(3, 0), None);
+        assert_eq!(grid.set(0, 1, 'x'), Some('d'));
+        assert_eq!(grid.set(0, 2, 'x'), None);
+        assert_eq!(grid[(0, 1)], 'x');
+        assert_eq!(grid.get_point(Point::new(1, 1)), Some(&'e'));
+        assert_eq!(grid.get_point(Point::new(-1, 0)), None);
+        assert!(!grid.contains_point(Point::new(0, 2)));
+        assert_eq!(grid.column(1).collect::<String>(), "be");
+        assert_eq!(grid.find(|c| *c == 'e'), Some((1, 1)));
+        assert!(Grid::parse("abc\nde", |c| c).is_err());
+    }
+
+    #[test]
+    fn test_neighbours() {
+        let grid = sample();
+        let n4: Vec<_> = grid.neighbours4(0, 0).collect();
+        assert_eq!(n4, [(1, 0), (0, 1)]);
+        let n8: Vec<_> = grid.neighbours8(1, 0).collect();
+        assert_eq!(n8, [(2, 0), (2, 1), (1, 1), (0, 1), (0, 0)]);
+    }
+
+    #[test]
+    fn test_transforms() {
+        let grid = sample();
+        assert_eq!(to_string(&grid.transpose()), "ad\nbe\ncf");
+        assert_eq!(to_string(&grid.rotate_cw()), "da\neb\nfc");
+        assert_eq!(to_string(&grid.rotate_ccw()), "cf\nbe\nad");
+        assert_eq!(to_string(&grid.flip_horizontal()), "cba\nfed");
+        assert_eq!(to_string(&grid.flip_vertical()), "def\nabc");
+        assert_eq!(grid.rotate_cw().rotate_ccw(), grid);
+        assert_eq!(to_string(&grid.crop((1, 0), (5, 0))), "bc");
+        assert_eq!(to_string(&grid.crop((1, 1), (1, 1))), "e");
+        assert_eq!(grid.crop((4, 0), (5, 5)), Grid::default());
+    }
+
+    #[test]
+    fn test_regions() {
+        let grid = Grid::parse(
+            "aab.
+.ab.
+b..b",
+            |c| c,
+        )
+        .unwrap();
+        let regions = grid.regions(&Dir::CARDINAL, |c| *c == 'a');
+        assert_eq!(regions.len(), 1);
+        assert_eq!(regions[0].cells, [(0, 0), (1, 0), (1, 1)]);
+        assert_eq!((regions[0].min, regions[0].max), ((0, 0), (1, 1)));
+
+        let bs = grid.regions(&Dir::CARDINAL, |c| *c == 'b');
+        assert_eq!(bs.len(), 3);
+        let bs = grid.regions(&Dir::ALL, |c| *c == 'b');
+        assert_eq!(bs.len(), 2);
+        let dots = grid.regions(&Dir::ALL, |c| *c == '.');
+        assert_eq!(dots.len(), 1);
+
+        let filled = grid
+            .flood_fill((2, 1), &Dir::CARDINAL, |c| *c == 'b')
+            .unwrap();
+        assert_eq!(filled.cells, [(2, 0), (2, 1)]);
+        assert!(grid
+            .flood_fill((0, 1), &Dir::CARDINAL, |c| *c == 'b')
+            .is_none());
+
+        let neighbours = grid.region_neighbours(&filled, &Dir::CARDINAL);
+        assert_eq!(neighbours, [(1, 0), (3, 0), (1, 1), (3, 1), (2, 2)]);
+    }
+}
@@ -0,0 +1,300 @@
+use std::{
+    fs::File,
+    io::{BufWriter, ErrorKind, Write},
+    path::Path,
+};
+
+use anyhow::{anyhow, bail, Context};
+
+use crate::grid::Grid;
+
+/// An 8-bit-per-channel colour.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct Rgb(pub u8, pub u8, pub u8);
+
+impl Rgb {
+    pub const BLACK: Rgb = Rgb(0, 0, 0);
+    pub const WHITE: Rgb = Rgb(255, 255, 255);
+    pub const GREY: Rgb = Rgb(64, 64, 64);
+    pub const RED: Rgb = Rgb(220, 50, 47);
+    pub const GREEN: Rgb = Rgb(80, 200, 80);
+    pub const YELLOW: Rgb = Rgb(240, 200, 40);
+    pub const MAGENTA: Rgb = Rgb(230, 60, 230);
+}
+
+/// The most pixels an image may have, so that a large scale fails cleanly rather than
+/// exhausting memory.
+pub const MAX_PIXELS: usize = 1 << 26;
+
+/// A bitmap that can be written as PPM or PNG without any image dependencies.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Image {
+    pixels: Grid<Rgb>,
+}
+
+impl Image {
+    /// Draws each grid cell as a `scale` × `scale` square coloured by `color`. Fails if the
+    /// image would be wider or taller than PNG allows, or have more than [`MAX_PIXELS`].
+    pub fn from_grid<T>(
+        grid: &Grid<T>,
+        scale: usize,
+        mut color: impl FnMut(&T) -> Rgb,
+    ) -> anyhow::Result<Image> {
+        let too_large = || {
+            anyhow!(
+                "A {}x{} grid at scale {scale} is too large for an image",
+                grid.width(),
+                grid.height()
+            )
+        };
+        let width = grid.width().checked_mul(scale).ok_or_else(too_large)?;
+        let height = grid.height().checked_mul(scale).ok_or_else(too_large)?;
+        let area = width.checked_mul(height).ok_or_else(too_large)?;
+        if u32::try_from(width).is_err() || u32::try_from(height).is_err() || area > MAX_PIXELS {
+            return Err(too_large());
+        }
+
+        let colors = grid.map(&mut color);
+        let mut pixels = Grid::new(width, height, Rgb::BLACK);
+        for ((x, y), c) in colors.iter() {
+            for dy in 0..scale {
+                for dx in 0..scale {
+                    pixels[(x * scale + dx, y * scale + dy)] = *c;
+                }
+            }
+        }
+        Ok(Image { pixels })
+    }
+
+    pub fn width(&self) -> usize {
+        self.pixels.width()
+    }
+
+    pub fn height(&self) -> usize {
+        self.pixels.height()
+    }
+
+    pub fn pixel(&self, x: usize, y: usize) -> Option<Rgb> {
+        self.pixels.get(x, y).copied()
+    }
+
+    /// Writes a binary (`P6`) PPM.
+    pub fn write_ppm(&self, mut out: impl Write) -> std::io::Result<()> {
+        write!(out, "P6\n{} {}\n255\n", self.width(), self.height())?;
+        for (_, Rgb(r, g, b)) in self.pixels.iter() {
+            out.write_all(&[*r, *g, *b])?;
+        }
+        Ok(())
+    }
+
+    /// Writes a truecolour PNG. The image data is stored uncompressed, so files are large but valid.
+    pub fn write_png(&self, mut out: impl Write) -> std::io::Result<()> {
+        out.write_all(b"\x89PNG\r\n\x1a\n")?;
+
+        let dimension = |n: usize| {
+            u32::try_from(n).map_err(|_| {
+                std::io::Error::new(ErrorKind::InvalidInput, "image too large for PNG")
+            })
+        };
+        let mut header = Vec::with_capacity(13);
+        header.extend(dimension(self.width())?.to_be_bytes());
+        header.extend(dimension(self.height())?.to_be_bytes());
+        // 8 bits per channel, truecolour, deflate, adaptive filtering, no interlace.
+        header.extend([8, 2, 0, 0, 0]);
+        write_chunk(&mut out, b"IHDR", &header)?;
+
+        let mut raw = Vec::with_capacity(self.height() * (1 + 3 * self.width()));
+        for row in self.pixels.rows() {
+            // Filter type 0: the scanline is stored as-is.
+            raw.push(0);
+            for Rgb(r, g, b) in row {
+                raw.extend([*r, *g, *b]);
+            }
+        }
+        write_chunk(&mut out, b"IDAT", &zlib_stored(&raw))?;
+        write_chunk(&mut out, b"IEND", &[])
+    }
+
+    /// Saves as PNG or PPM depending on the extension of `path`. Empty images are rejected,
+    /// since neither format can hold one usefully.
+    pub fn save(&self, path: impl AsRef<Path>) -> anyhow::Result<()> {
+        let path = path.as_ref();
+        if self.width() == 0 || self.height() == 0 {
+            bail!(
+                "Refusing to write {}: the image is {}x{}",
+                path.display(),
+                self.width(),
+                self.height()
+            );
+        }
+        let extension = path.extension().and_then(|e| e.to_str()).unwrap_or("");
+        let write =
+            |f: fn(&Image, &mut BufWriter<File>) -> std::io::Result<()>| -> anyhow::Result<()> {
+                let mut out = BufWriter::new(File::create(path)?);
+                f(self, &mut out)?;
+                out.flush()?;
+                Ok(())
+            };
+        match extension.to_ascii_lowercase().as_str() {
+            "png" => write(|image, out| image.write_png(out)),
+            "ppm" => write(|image, out| image.write_ppm(out)),
+            _ => bail!("Unsupported image format `{extension}`: expected .png or .ppm"),
+        }
+        .with_context(|| format!("Failed to write {}", path.display()))
+    }
+}
+
+fn write_chunk(out: &mut impl Write, kind: &[u8; 4], data: &[u8]) -> std::io::Result<()> {
+    out.write_all(&(data.len() as u32).to_be_bytes())?;
+    out.write_all(kind)?;
+    out.write_all(data)?;
+    let mut crc = Crc32::default();
+    crc.update(kind);
+    crc.update(data);
+    out.write_all(&crc.finish().to_be_bytes())
+}
+
+/// Wraps `data` in a zlib stream made of uncompressed deflate blocks.
+fn zlib_stored(data: &[u8]) -> Vec<u8> {
+    const MAX_BLOCK: usize = u16::MAX as usize;
+    let mut out = Vec::with_capacity(data.len() + data.len() / MAX_BLOCK * 5 + 11);
+    // CMF/FLG: deflate with a 32K window, no preset dictionary, fastest compression.
+    out.extend([0x78, 0x01]);
+    let mut blocks = data.chunks(MAX_BLOCK).peekable();
+    if blocks.peek().is_none() {
+        out.extend([1, 0, 0, 0xff, 0xff]);
+    }
+    while let Some(block) = blocks.next() {
+        let last = blocks.peek().is_none();
+        let len = block.len() as u16;
+        out.push(u8::from(last));
+        out.extend(len.to_le_bytes());
+        out.extend((!len).to_le_bytes());
+        out.extend(block);
+    }
+    out.extend(adler32(data).to_be_bytes());
+    out
+}
+
+fn adler32(data: &[u8]) -> u32 {
+    const MOD: u32 = 65521;
+    let (mut a, mut b) = (1u32, 0u32);
+    // 5552 is the most bytes that can be summed before `b` could overflow.
+    for chunk in data.chunks(5552) {
+        for byte in chunk {
+            a += u32::from(*byte);
+            b += a;
+        }
+        a %= MOD;
+        b %= MOD;
+    }
+    (b << 16) | a
+}
+
+/// The CRC-32 used by PNG chunks (IEEE polynomial, reflected).
+struct Crc32 {
+    table: [u32; 256],
+    value: u32,
+}
+
+impl Default for Crc32 {
+    fn default() -> Self {
+        let mut table = [0; 256];
+        for (n, entry) in table.iter_mut().enumerate() {
+            let mut c = n as u32;
+            for _ in 0..8 {
+                c = if c & 1 == 1 {
+                    0xedb8_8320 ^ (c >> 1)
+                } else {
+                    c >> 1
+                };
+            }
+            *entry = c;
+        }
+        Crc32 {
+            table,
+            value: 0xffff_ffff,
+        }
+    }
+}
+
+impl Crc32 {
+    fn update(&mut self, data: &[u8]) {
+        for byte in data {
+            self.value =
+                self.table[((self.value ^ u32::from(*byte)) & 0xff) as usize] ^ (self.value >> 8);
+        }
+    }
+
+    fn finish(&self) -> u32 {
+        self.value ^ 0xffff_ffff
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn checkerboard() -> Image {
+        let grid = Grid::parse("#.\n.#", |c| c == '#').unwrap();
+        Image::from_grid(&grid, 2, |on| if *on { Rgb::WHITE } else { Rgb::BLACK }).unwrap()
+    }
+
+    #[test]
+    fn test_from_grid() {
+        let image = checkerboard();
+        assert_eq!((image.width(), image.height()), (4, 4));
+        assert_eq!(image.pixel(1, 1), Some(Rgb::WHITE));
+        assert_eq!(image.pixel(2, 1), Some(Rgb::BLACK));
+        assert_eq!(image.pixel(3, 3), Some(Rgb::WHITE));
+        assert_eq!(image.pixel(4, 0), None);
+
+        let grid = Grid::new(3, 2, false);
+        assert!(Image::from_grid(&grid, usize::MAX / 2, |_| Rgb::BLACK).is_err());
+        assert!(Image::from_grid(&grid, 100_000, |_| Rgb::BLACK).is_err());
+    }
+
+    #[test]
+    fn test_ppm() {
+        let mut out = Vec::new();
+        checkerboard().write_ppm(&mut out).unwrap();
+        assert!(out.starts_with(b"P6\n4 4\n255\n"));
+        assert_eq!(out.len(), 11 + 4 * 4 * 3);
+        assert_eq!(&out[11..14], [255, 255, 255]);
+    }
+
+    #[test]
+    fn test_checksums() {
+        let mut crc = Crc32::default();
+        crc.update(b"IEND");
+        assert_eq!(crc.finish(), 0xae42_6082);
+        assert_eq!(adler32(b"Wikipedia"), 0x11e6_0398);
+        assert_eq!(adler32(&[]), 1);
+    }
+
+    #[test]
+    fn test_save() {
+        let empty = Image::from_grid(&Grid::<bool>::default(), 4, |_| Rgb::BLACK).unwrap();
+        let path = std::env::temp_dir().join("aoc2023-test-empty.png");
+        assert!(empty.save(&path).is_err());
+        assert!(!path.exists());
+        assert!(checkerboard().save("checkerboard.gif").is_err());
+    }
+
+    #[test]
+    fn test_png() {
+        let mut out = Vec::new();
+        checkerboard().write_png(&mut out).unwrap();
+        assert!(out.starts_with(b"\x89PNG\r\n\x1a\n"));
+        assert_eq!(&out[12..16], b"IHDR");
+        assert_eq!(&out[16..24], [0, 0, 0, 4, 0, 0, 0, 4]);
+        // An empty IEND chunk always ends with the same CRC.
+        assert!(out.ends_with(b"\0\0\0\0IEND\xae\x42\x60\x82"));
+
+        let raw = vec![7; 70_000];
+        let stream = zlib_stored(&raw);
+        assert_eq!(stream.len(), 2 + 2 * 5 + raw.len() + 4);
+        assert_eq!(stream[2], 0);
+        assert_eq!(stream[2 + 5 + 65535], 1);
+    }
+}
@@ -0,0 +1,13 @@
+pub mod answers;
+pub mod bench;
+pub mod cli;
+pub mod days;
+pub mod dir;
+pub mod grid;
+pub mod image;
+pub mod point;
+pub mod search;
+pub mod solution;
+pub mod sparse;
+pub mod term;
+pub mod util;
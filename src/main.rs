@@ -1,15 +1,416 @@
-mod days;
-mod util;
+use std::{
+    fs::File,
+    io::{BufRead, BufReader},
+    path::{Path, PathBuf},
+    process::ExitCode,
+    time::{Duration, Instant},
+};
 
-pub use days::*;
+use anyhow::{bail, Context};
+use aoc2023::{
+    answers::{AnswerStore, Check, ANSWERS_PATH},
+    bench::{BenchReport, Stats},
+    cli::{self, Command, Target},
+    days::{self, CellKind, Config, GearSpec, RenderOptions, Schematic, Vocabulary},
+    image::Image,
+    solution::{Answers, Part},
+    util::{example_path, input_path, read_example, read_input},
+};
 
-fn main() {
-    let days: Vec<&dyn Fn() -> ()> = vec![&day01, &day02, &day03, &day04, &day05];
+fn main() -> anyhow::Result<ExitCode> {
+    let command = Command::parse(std::env::args().skip(1))?;
+    let implemented: Vec<usize> = days::registry(&Config::default())
+        .iter()
+        .map(|s| s.day())
+        .collect();
 
-    std::env::args().skip(1).for_each(|arg| {
-        let day = arg.parse::<usize>().unwrap();
-        println!("--- Day {day:02} ---");
-        days[day - 1]();
-        println!("")
-    });
+    match command {
+        Command::Run {
+            selectors,
+            save,
+            explain,
+            bag,
+        } => {
+            let config = Config { bag };
+            run(
+                &cli::resolve(&selectors, &implemented)?,
+                &config,
+                save,
+                explain,
+            )
+        }
+        Command::Check(selectors) => check(&cli::resolve(&selectors, &implemented)?),
+        Command::Test(selectors) => test(&cli::resolve(&selectors, &implemented)?),
+        Command::Bench {
+            selectors,
+            iterations,
+        } => bench(&cli::resolve(&selectors, &implemented)?, iterations),
+        Command::Render {
+            example,
+            crop,
+            color,
+            image,
+            scale,
+        } => match image {
+            Some(path) => render_image(example, crop, &path, scale),
+            None => render(example, crop, color.enabled()),
+        },
+        Command::Calibrate {
+            path,
+            vocab,
+            explain,
+        } => calibrate(path.as_deref(), &vocab, explain),
+        Command::List => {
+            for day in implemented {
+                println!("Day {day:02}");
+            }
+            Ok(ExitCode::SUCCESS)
+        }
+        Command::Help => {
+            println!("{}", cli::USAGE);
+            Ok(ExitCode::SUCCESS)
+        }
+    }
+}
+
+fn run(targets: &[Target], config: &Config, save: bool, explain: bool) -> anyhow::Result<ExitCode> {
+    // Only touch the answers file when asked to save into it.
+    let mut store = match save {
+        true => Some(AnswerStore::load(ANSWERS_PATH)?),
+        false => None,
+    };
+    let mut failures = 0;
+    for target in targets {
+        println!("--- Day {:02} ---", target.day);
+        if explain {
+            if let Err(err) = print_explanation(target, config) {
+                failures += 1;
+                println!("FAILED: {err:#}");
+                println!();
+                continue;
+            }
+        }
+        match solve(target, config, read_input, input_path) {
+            Ok(answers) => {
+                for part in &target.parts {
+                    let answer = answers.get(*part).unwrap_or("-");
+                    println!("Part {part}: {answer}");
+                    if let Some(store) = &mut store {
+                        store.set(target.day, *part, answer);
+                    }
+                }
+            }
+            Err(err) => {
+                failures += 1;
+                println!("FAILED: {err:#}");
+            }
+        }
+        println!()
+    }
+
+    if let Some(store) = store {
+        store.save(ANSWERS_PATH)?;
+        println!("Saved answers to {ANSWERS_PATH}");
+    }
+    Ok(exit_code(failures))
+}
+
+fn check(targets: &[Target]) -> anyhow::Result<ExitCode> {
+    let store = AnswerStore::load(ANSWERS_PATH)?;
+    let (mut passed, mut changed, mut failed, mut missing) = (0, 0, 0, 0);
+    for target in targets {
+        let answers = match solve(target, &Config::default(), read_input, input_path) {
+            Ok(answers) => answers,
+            Err(err) => {
+                failed += target.parts.len();
+                println!("Day {:02}: FAIL {err:#}", target.day);
+                continue;
+            }
+        };
+        for part in &target.parts {
+            let actual = answers.get(*part).unwrap_or("-");
+            match store.check(target.day, *part, actual) {
+                Check::Pass => {
+                    passed += 1;
+                    println!("Day {:02} part {part}: pass", target.day);
+                }
+                Check::Changed { expected } => {
+                    changed += 1;
+                    println!(
+                        "Day {:02} part {part}: CHANGED (expected {expected}, got {actual})",
+                        target.day
+                    );
+                }
+                Check::Missing => {
+                    missing += 1;
+                    println!(
+                        "Day {:02} part {part}: no stored answer (got {actual})",
+                        target.day
+                    );
+                }
+            }
+        }
+    }
+
+    println!();
+    println!("{passed} passed, {changed} changed, {failed} failed, {missing} missing");
+    if changed + failed > 0 {
+        return Ok(ExitCode::FAILURE);
+    }
+    Ok(ExitCode::SUCCESS)
+}
+
+/// Reads one day's input and solves the requested parts, attaching the input path to any error.
+fn solve(
+    target: &Target,
+    config: &Config,
+    read: fn(usize, usize) -> anyhow::Result<String>,
+    path: fn(usize, usize) -> PathBuf,
+) -> anyhow::Result<Answers> {
+    let solution = days::find(target.day, config).expect("resolved day is registered");
+    let input = read(target.day, 1)?;
+    solution
+        .solve(&input, &target.parts)
+        .with_context(|| path(target.day, 1).display().to_string())
+}
+
+fn print_explanation(target: &Target, config: &Config) -> anyhow::Result<()> {
+    let solution = days::find(target.day, config).expect("resolved day is registered");
+    let input = read_input(target.day, 1)?;
+    for part in &target.parts {
+        let explanation = solution
+            .explain(&input, *part)
+            .with_context(|| input_path(target.day, 1).display().to_string())?;
+        match explanation {
+            Some(trace) => {
+                println!("Part {part} trace:");
+                print!("{trace}");
+            }
+            None => println!("Part {part}: no trace available"),
+        }
+    }
+    Ok(())
+}
+
+/// Prints the day 3 schematic with part numbers, ignored numbers, symbols and gears highlighted.
+fn render(
+    example: bool,
+    crop: Option<((usize, usize), (usize, usize))>,
+    color: bool,
+) -> anyhow::Result<ExitCode> {
+    let schematic = load_schematic(example)?;
+    check_crop(&schematic, crop)?;
+    let options = RenderOptions {
+        crop,
+        color,
+        ..Default::default()
+    };
+    print!("{}", schematic.render(&options));
+    if color {
+        let legend = [
+            ("part number", CellKind::PartNumber),
+            ("ignored number", CellKind::IgnoredNumber),
+            ("symbol", CellKind::Symbol),
+            ("gear", CellKind::Gear),
+        ]
+        .map(|(label, kind)| kind.style().paint(label, true));
+        println!("{}", legend.join("  "));
+    }
+    Ok(ExitCode::SUCCESS)
+}
+
+/// Saves the day 3 schematic as an image, one `scale`-pixel square per cell.
+fn render_image(
+    example: bool,
+    crop: Option<((usize, usize), (usize, usize))>,
+    path: &Path,
+    scale: usize,
+) -> anyhow::Result<ExitCode> {
+    let schematic = load_schematic(example)?;
+    check_crop(&schematic, crop)?;
+    let mut kinds = schematic.classify(&GearSpec::default());
+    if let Some((min, max)) = crop {
+        kinds = kinds.crop(min, max);
+    }
+    let image = Image::from_grid(&kinds, scale, |kind| kind.rgb())?;
+    image.save(path)?;
+    println!(
+        "Wrote {}x{} image to {}",
+        image.width(),
+        image.height(),
+        path.display()
+    );
+    Ok(ExitCode::SUCCESS)
+}
+
+/// Fails if `crop` starts outside the schematic, which would otherwise draw nothing.
+fn check_crop(
+    schematic: &Schematic,
+    crop: Option<((usize, usize), (usize, usize))>,
+) -> anyhow::Result<()> {
+    let grid = schematic.grid();
+    if let Some(((x, y), _)) = crop {
+        if x >= grid.width() || y >= grid.height() {
+            bail!(
+                "Crop starting at {x},{y} is outside the {}x{} schematic",
+                grid.width(),
+                grid.height()
+            );
+        }
+    }
+    Ok(())
+}
+
+fn load_schematic(example: bool) -> anyhow::Result<Schematic> {
+    let (input, path) = match example {
+        true => (read_example(3, 1)?, example_path(3, 1)),
+        false => (read_input(3, 1)?, input_path(3, 1)),
+    };
+    input.parse().with_context(|| path.display().to_string())
+}
+
+/// Runs the day 1 calibration over any file or stdin with a chosen number-word vocabulary.
+/// Without `--explain` the input is streamed, so it may be larger than memory.
+fn calibrate(path: Option<&Path>, vocab: &str, explain: bool) -> anyhow::Result<ExitCode> {
+    let calibrator = Vocabulary::resolve(vocab)?.calibrator()?;
+    let name = path.map_or("stdin".to_string(), |p| p.display().to_string());
+    let reader: Box<dyn BufRead> = match path {
+        Some(path) => Box::new(BufReader::with_capacity(
+            1 << 16,
+            File::open(path).with_context(|| format!("Failed to read {name}"))?,
+        )),
+        None => Box::new(std::io::stdin().lock()),
+    };
+
+    if explain {
+        let lines = reader
+            .lines()
+            .collect::<Result<Vec<String>, _>>()
+            .with_context(|| format!("Failed to read {name}"))?;
+        print!("{}", calibrator.explain(&lines));
+        return Ok(ExitCode::SUCCESS);
+    }
+
+    let start = Instant::now();
+    let summary = calibrator.calibrate_stream(reader).context(name)?;
+    let elapsed = start.elapsed();
+    println!("Total: {}", summary.total);
+    let mib = summary.bytes as f64 / (1024.0 * 1024.0);
+    eprintln!(
+        "{} lines, {mib:.1} MiB in {elapsed:.2?} ({:.1} MiB/s)",
+        summary.lines,
+        mib / elapsed.as_secs_f64().max(f64::EPSILON)
+    );
+    Ok(ExitCode::SUCCESS)
+}
+
+fn exit_code(failures: usize) -> ExitCode {
+    if failures > 0 {
+        println!("{failures} failure(s)");
+        return ExitCode::FAILURE;
+    }
+    ExitCode::SUCCESS
+}
+
+fn test(targets: &[Target]) -> anyhow::Result<ExitCode> {
+    let mut failures = 0;
+    for target in targets {
+        let solution =
+            days::find(target.day, &Config::default()).expect("resolved day is registered");
+        println!("--- Day {:02} ---", target.day);
+        if !example_path(target.day, 1).exists() {
+            println!("No example input");
+            println!();
+            continue;
+        }
+        let answers = match solve(target, &Config::default(), read_example, example_path) {
+            Ok(answers) => answers,
+            Err(err) => {
+                failures += 1;
+                println!("FAILED: {err:#}");
+                println!();
+                continue;
+            }
+        };
+        for part in &target.parts {
+            let actual = answers.get(*part).unwrap_or("-");
+            match solution.example_answer(*part) {
+                Some(expected) if expected == actual => println!("Part {part}: ok ({actual})"),
+                Some(expected) => {
+                    failures += 1;
+                    println!("Part {part}: FAILED (expected {expected}, got {actual})");
+                }
+                None => println!("Part {part}: {actual} (no expected answer)"),
+            }
+        }
+        println!()
+    }
+    Ok(exit_code(failures))
+}
+
+fn bench(targets: &[Target], iterations: usize) -> anyhow::Result<ExitCode> {
+    let mut failures = 0;
+    let mut reports = Vec::new();
+    for target in targets {
+        println!("--- Day {:02} ---", target.day);
+        let report = read_input(target.day, 1).and_then(|input| {
+            let solution =
+                days::find(target.day, &Config::default()).expect("resolved day is registered");
+            solution
+                .bench(&input, &target.parts, iterations)
+                .with_context(|| input_path(target.day, 1).display().to_string())
+        });
+        match report {
+            Ok(report) => {
+                println!("{:<8} {:>12} {:>12} {:>12}", "", "min", "median", "mean");
+                print_stats("parse", report.parse);
+                for (part, stats) in &report.parts {
+                    print_stats(&format!("part {part}"), *stats);
+                }
+                reports.push(report);
+            }
+            Err(err) => {
+                failures += 1;
+                println!("FAILED: {err:#}");
+            }
+        }
+        println!()
+    }
+
+    println!("Summary (median of {iterations} runs)");
+    println!(
+        "{:<6} {:>12} {:>12} {:>12} {:>12}",
+        "Day", "parse", "part 1", "part 2", "total"
+    );
+    let stage =
+        |stats: Option<Stats>| stats.map_or("-".to_string(), |s| format!("{:.2?}", s.median));
+    for report in &reports {
+        println!(
+            "{:<6} {:>12} {:>12} {:>12} {:>12}",
+            format!("{:02}", report.day),
+            stage(Some(report.parse)),
+            stage(report.part(Part::One)),
+            stage(report.part(Part::Two)),
+            format!("{:.2?}", report.total()),
+        );
+    }
+    let total: Duration = reports.iter().map(BenchReport::total).sum();
+    println!(
+        "{:<6} {:>12} {:>12} {:>12} {:>12}",
+        "all",
+        "",
+        "",
+        "",
+        format!("{total:.2?}")
+    );
+    Ok(exit_code(failures))
+}
+
+fn print_stats(label: &str, stats: Stats) {
+    println!(
+        "{label:<8} {:>12} {:>12} {:>12}",
+        format!("{:.2?}", stats.min),
+        format!("{:.2?}", stats.median),
+        format!("{:.2?}", stats.mean),
+    );
 }
@@ -0,0 +1,299 @@
+use std::{
+    cmp::Reverse,
+    collections::{BinaryHeap, HashMap, VecDeque},
+    hash::Hash,
+};
+
+/// A route from a start node to a goal, including both ends.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Path<N> {
+    pub cost: u64,
+    pub nodes: Vec<N>,
+}
+
+/// Distances from a start node to everything reachable, with predecessors for path reconstruction.
+#[derive(Debug, Clone)]
+pub struct Distances<N> {
+    start: N,
+    dist: HashMap<N, u64>,
+    prev: HashMap<N, N>,
+}
+
+impl<N: Clone + Eq + Hash> Distances<N> {
+    fn new(start: N) -> Distances<N> {
+        let mut dist = HashMap::new();
+        dist.insert(start.clone(), 0);
+        Distances {
+            start,
+            dist,
+            prev: HashMap::new(),
+        }
+    }
+
+    pub fn distance(&self, node: &N) -> Option<u64> {
+        self.dist.get(node).copied()
+    }
+
+    pub fn reachable(&self) -> impl Iterator<Item = (&N, u64)> {
+        self.dist.iter().map(|(n, d)| (n, *d))
+    }
+
+    /// Walks predecessors back from `goal` to the start.
+    pub fn path_to(&self, goal: &N) -> Option<Path<N>> {
+        let cost = self.distance(goal)?;
+        let mut nodes = vec![goal.clone()];
+        let mut current = goal;
+        while *current != self.start {
+            current = self.prev.get(current)?;
+            nodes.push(current.clone());
+        }
+        nodes.reverse();
+        Some(Path { cost, nodes })
+    }
+}
+
+/// Breadth-first search where every edge costs 1.
+pub fn bfs<N, I>(start: N, mut neighbours: impl FnMut(&N) -> I) -> Distances<N>
+where
+    N: Clone + Eq + Hash,
+    I: IntoIterator<Item = N>,
+{
+    let mut result = Distances::new(start.clone());
+    let mut queue = VecDeque::from([start]);
+    while let Some(node) = queue.pop_front() {
+        let d = result.dist[&node];
+        for next in neighbours(&node) {
+            if result.dist.contains_key(&next) {
+                continue;
+            }
+            result.dist.insert(next.clone(), d + 1);
+            result.prev.insert(next.clone(), node.clone());
+            queue.push_back(next);
+        }
+    }
+    result
+}
+
+/// Breadth-first search that stops at the first node satisfying `is_goal`.
+pub fn bfs_to<N, I>(
+    start: N,
+    mut is_goal: impl FnMut(&N) -> bool,
+    mut neighbours: impl FnMut(&N) -> I,
+) -> Option<Path<N>>
+where
+    N: Clone + Eq + Hash,
+    I: IntoIterator<Item = N>,
+{
+    astar(
+        start,
+        &mut is_goal,
+        |n| neighbours(n).into_iter().map(|n| (n, 1)),
+        |_| 0,
+    )
+}
+
+/// Dijkstra's algorithm over non-negative edge weights, exploring everything reachable.
+pub fn dijkstra<N, I>(start: N, mut neighbours: impl FnMut(&N) -> I) -> Distances<N>
+where
+    N: Clone + Eq + Hash,
+    I: IntoIterator<Item = (N, u64)>,
+{
+    let mut result = Distances::new(start.clone());
+    let mut frontier = Frontier::default();
+    frontier.push(0, start);
+    while let Some((d, node)) = frontier.pop() {
+        if result.dist.get(&node).is_some_and(|best| *best < d) {
+            continue;
+        }
+        for (next, weight) in neighbours(&node) {
+            let nd = d + weight;
+            if result.dist.get(&next).is_some_and(|best| *best <= nd) {
+                continue;
+            }
+            result.dist.insert(next.clone(), nd);
+            result.prev.insert(next.clone(), node.clone());
+            frontier.push(nd, next);
+        }
+    }
+    result
+}
+
+/// Dijkstra's algorithm that stops at the first node satisfying `is_goal`.
+pub fn dijkstra_to<N, I>(
+    start: N,
+    is_goal: impl FnMut(&N) -> bool,
+    neighbours: impl FnMut(&N) -> I,
+) -> Option<Path<N>>
+where
+    N: Clone + Eq + Hash,
+    I: IntoIterator<Item = (N, u64)>,
+{
+    astar(start, is_goal, neighbours, |_| 0)
+}
+
+/// A* search. `heuristic` must never overestimate the remaining cost for the path to be optimal.
+pub fn astar<N, I>(
+    start: N,
+    mut is_goal: impl FnMut(&N) -> bool,
+    mut neighbours: impl FnMut(&N) -> I,
+    mut heuristic: impl FnMut(&N) -> u64,
+) -> Option<Path<N>>
+where
+    N: Clone + Eq + Hash,
+    I: IntoIterator<Item = (N, u64)>,
+{
+    let mut result = Distances::new(start.clone());
+    // Entries carry the distance they were queued with, so outdated ones can be skipped.
+    let mut frontier = Frontier::default();
+    frontier.push(heuristic(&start), (0, start));
+    while let Some((_, (d, node))) = frontier.pop() {
+        if result.dist.get(&node).is_some_and(|best| *best < d) {
+            continue;
+        }
+        if is_goal(&node) {
+            return result.path_to(&node);
+        }
+        for (next, weight) in neighbours(&node) {
+            let nd = d + weight;
+            if result.dist.get(&next).is_some_and(|best| *best <= nd) {
+                continue;
+            }
+            result.dist.insert(next.clone(), nd);
+            result.prev.insert(next.clone(), node.clone());
+            let priority = nd + heuristic(&next);
+            frontier.push(priority, (nd, next));
+        }
+    }
+    None
+}
+
+/// Min-priority queue that doesn't require the nodes themselves to be ordered.
+struct Frontier<N> {
+    heap: BinaryHeap<Reverse<(u64, usize)>>,
+    nodes: Vec<Option<N>>,
+}
+
+impl<N> Default for Frontier<N> {
+    fn default() -> Self {
+        Frontier {
+            heap: BinaryHeap::new(),
+            nodes: Vec::new(),
+        }
+    }
+}
+
+impl<N> Frontier<N> {
+    fn push(&mut self, priority: u64, node: N) {
+        self.heap.push(Reverse((priority, self.nodes.len())));
+        self.nodes.push(Some(node));
+    }
+
+    fn pop(&mut self) -> Option<(u64, N)> {
+        let Reverse((priority, id)) = self.heap.pop()?;
+        Some((priority, self.nodes[id].take()?))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{grid::Grid, point::Point};
+
+    use super::*;
+
+    const MAZE: &str = "\
+S.#.....
+.##.###.
+...#...#
+.#...#.G";
+
+    fn maze() -> (Grid<char>, (usize, usize), (usize, usize)) {
+        let grid = Grid::parse(MAZE, |c| c).unwrap();
+        let start = grid.find(|c| *c == 'S').unwrap();
+        let goal = grid.find(|c| *c == 'G').unwrap();
+        (grid, start, goal)
+    }
+
+    fn open(grid: &Grid<char>, (x, y): (usize, usize)) -> Vec<(usize, usize)> {
+        grid.neighbours4(x, y).filter(|n| grid[*n] != '#').collect()
+    }
+
+    #[test]
+    fn test_bfs() {
+        let (grid, start, goal) = maze();
+        let distances = bfs(start, |p| open(&grid, *p));
+        assert_eq!(distances.distance(&goal), Some(12));
+        assert_eq!(distances.distance(&(2, 0)), None);
+
+        let path = distances.path_to(&goal).unwrap();
+        assert_eq!(path.nodes.len(), 13);
+        assert_eq!(path.nodes.first(), Some(&start));
+        assert_eq!(path.nodes.last(), Some(&goal));
+        assert!(path
+            .nodes
+            .windows(2)
+            .all(|w| Point::from(w[0]).manhattan(Point::from(w[1])) == 1));
+
+        let early = bfs_to(start, |p| *p == goal, |p| open(&grid, *p)).unwrap();
+        assert_eq!(early.cost, 12);
+    }
+
+    #[test]
+    fn test_weighted() {
+        // Stepping onto a digit costs that digit; everything else costs 1.
+        let grid = Grid::parse("S19\n1#1\n11G", |c| c).unwrap();
+        let cost = |c: char| c.to_digit(10).map_or(1, u64::from);
+        let neighbours = |p: &(usize, usize)| {
+            open(&grid, *p)
+                .into_iter()
+                .map(|n| (n, cost(grid[n])))
+                .collect::<Vec<_>>()
+        };
+        let goal = (2, 2);
+
+        let distances = dijkstra((0, 0), neighbours);
+        assert_eq!(distances.distance(&goal), Some(4));
+        assert_eq!(
+            distances.path_to(&goal).unwrap().nodes,
+            [(0, 0), (0, 1), (0, 2), (1, 2), (2, 2)]
+        );
+
+        let manhattan = |p: &(usize, usize)| Point::from(*p).manhattan(Point::from(goal));
+        let path = astar((0, 0), |p| *p == goal, neighbours, manhattan).unwrap();
+        assert_eq!(path.cost, 4);
+        assert_eq!(
+            dijkstra_to((0, 0), |p| *p == goal, neighbours)
+                .unwrap()
+                .cost,
+            4
+        );
+    }
+
+    #[test]
+    fn test_generic_graph() {
+        let edges: HashMap<&str, Vec<(&str, u64)>> = HashMap::from([
+            ("a", vec![("b", 7), ("c", 9), ("f", 14)]),
+            ("b", vec![("c", 10), ("d", 15)]),
+            ("c", vec![("d", 11), ("f", 2)]),
+            ("d", vec![("e", 6)]),
+            ("f", vec![("e", 9)]),
+        ]);
+        let neighbours = |n: &&str| edges.get(n).cloned().unwrap_or_default();
+        let path = dijkstra_to("a", |n| *n == "e", neighbours).unwrap();
+        assert_eq!(path.cost, 20);
+        assert_eq!(path.nodes, ["a", "c", "f", "e"]);
+        assert!(dijkstra_to("e", |n| *n == "a", neighbours).is_none());
+
+        // `f` is queued at 14 and again at 11; the outdated entry mustn't be expanded.
+        let mut expanded = Vec::new();
+        dijkstra_to(
+            "a",
+            |n| *n == "e",
+            |n| {
+                expanded.push(*n);
+                neighbours(n)
+            },
+        );
+        expanded.sort();
+        assert_eq!(expanded, ["a", "b", "c", "d", "f"]);
+    }
+}
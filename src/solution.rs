@@ -0,0 +1,126 @@
+use std::fmt::{self, Display};
+
+use anyhow::Context;
+
+use crate::bench::{self, BenchReport};
+
+/// A single day's puzzle. The input is parsed once and both parts are answered from it.
+pub trait Solution {
+    const DAY: usize;
+
+    /// Expected answers for `example01.txt`, used by the `test` command.
+    const EXAMPLE_ANSWERS: [Option<&'static str>; 2] = [None, None];
+
+    type Input;
+    type Answer1: Display;
+    type Answer2: Display;
+
+    fn parse(&self, input: &str) -> anyhow::Result<Self::Input>;
+    fn part1(&self, input: &Self::Input) -> anyhow::Result<Self::Answer1>;
+    fn part2(&self, input: &Self::Input) -> anyhow::Result<Self::Answer2>;
+
+    /// A human-readable trace of how `part` arrives at its answer, for days that support one.
+    fn explain(&self, _input: &Self::Input, _part: Part) -> anyhow::Result<Option<String>> {
+        Ok(None)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Part {
+    One,
+    Two,
+}
+
+impl Part {
+    pub const ALL: [Part; 2] = [Part::One, Part::Two];
+
+    pub fn number(self) -> usize {
+        match self {
+            Part::One => 1,
+            Part::Two => 2,
+        }
+    }
+}
+
+impl Display for Part {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.number())
+    }
+}
+
+/// Object-safe view of a [`Solution`], so days with different answer types can share a registry.
+pub trait DynSolution {
+    fn day(&self) -> usize;
+    fn example_answer(&self, part: Part) -> Option<&'static str>;
+    fn solve(&self, input: &str, parts: &[Part]) -> anyhow::Result<Answers>;
+    fn bench(&self, input: &str, parts: &[Part], iterations: usize) -> anyhow::Result<BenchReport>;
+    fn explain(&self, input: &str, part: Part) -> anyhow::Result<Option<String>>;
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Answers {
+    pub part1: Option<String>,
+    pub part2: Option<String>,
+}
+
+impl Answers {
+    pub fn get(&self, part: Part) -> Option<&str> {
+        match part {
+            Part::One => self.part1.as_deref(),
+            Part::Two => self.part2.as_deref(),
+        }
+    }
+}
+
+impl<S: Solution> DynSolution for S {
+    fn day(&self) -> usize {
+        S::DAY
+    }
+
+    fn example_answer(&self, part: Part) -> Option<&'static str> {
+        S::EXAMPLE_ANSWERS[part.number() - 1]
+    }
+
+    fn solve(&self, input: &str, parts: &[Part]) -> anyhow::Result<Answers> {
+        let parsed = self.parse(input).context("Failed to parse input")?;
+        let mut answers = Answers::default();
+        for part in parts {
+            match part {
+                Part::One => {
+                    let answer = self.part1(&parsed).context("Part 1 failed")?;
+                    answers.part1 = Some(answer.to_string());
+                }
+                Part::Two => {
+                    let answer = self.part2(&parsed).context("Part 2 failed")?;
+                    answers.part2 = Some(answer.to_string());
+                }
+            }
+        }
+        Ok(answers)
+    }
+
+    fn bench(&self, input: &str, parts: &[Part], iterations: usize) -> anyhow::Result<BenchReport> {
+        let parse =
+            bench::measure(iterations, || self.parse(input)).context("Failed to parse input")?;
+        let parsed = self.parse(input)?;
+        let mut report = BenchReport {
+            day: S::DAY,
+            parse,
+            parts: Vec::new(),
+        };
+        for part in parts {
+            let stats = match part {
+                Part::One => bench::measure(iterations, || self.part1(&parsed)),
+                Part::Two => bench::measure(iterations, || self.part2(&parsed)),
+            }
+            .with_context(|| format!("Part {part} failed"))?;
+            report.parts.push((*part, stats));
+        }
+        Ok(report)
+    }
+
+    fn explain(&self, input: &str, part: Part) -> anyhow::Result<Option<String>> {
+        let parsed = self.parse(input).context("Failed to parse input")?;
+        Solution::explain(self, &parsed, part).with_context(|| format!("Part {part} failed"))
+    }
+}
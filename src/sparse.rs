@@ -0,0 +1,217 @@
+use std::collections::HashMap;
+
+use crate::{dir::Dir, grid::Grid, point::Point};
+
+/// An unbounded grid that only stores occupied cells, keyed by signed [`Point`]s.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SparseGrid<T> {
+    cells: HashMap<Point, T>,
+    /// Inclusive corners of the occupied area, or `None` when empty.
+    bounds: Option<(Point, Point)>,
+}
+
+impl<T> Default for SparseGrid<T> {
+    fn default() -> Self {
+        SparseGrid {
+            cells: HashMap::new(),
+            bounds: None,
+        }
+    }
+}
+
+impl<T> SparseGrid<T> {
+    pub fn new() -> SparseGrid<T> {
+        SparseGrid::default()
+    }
+
+    /// Keeps the cells of a dense grid matching `keep`, at the same coordinates.
+    pub fn from_grid(grid: &Grid<T>, mut keep: impl FnMut(&T) -> bool) -> SparseGrid<T>
+    where
+        T: Clone,
+    {
+        grid.iter()
+            .filter(|(_, cell)| keep(cell))
+            .map(|(p, cell)| (Point::from(p), cell.clone()))
+            .collect()
+    }
+
+    pub fn len(&self) -> usize {
+        self.cells.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.cells.is_empty()
+    }
+
+    pub fn get(&self, p: Point) -> Option<&T> {
+        self.cells.get(&p)
+    }
+
+    pub fn get_mut(&mut self, p: Point) -> Option<&mut T> {
+        self.cells.get_mut(&p)
+    }
+
+    pub fn contains(&self, p: Point) -> bool {
+        self.cells.contains_key(&p)
+    }
+
+    /// Occupies `p`, growing the bounds if needed, and returns the value it replaced.
+    pub fn insert(&mut self, p: Point, value: T) -> Option<T> {
+        self.bounds = Some(match self.bounds {
+            None => (p, p),
+            Some((min, max)) => (
+                Point::new(min.x.min(p.x), min.y.min(p.y)),
+                Point::new(max.x.max(p.x), max.y.max(p.y)),
+            ),
+        });
+        self.cells.insert(p, value)
+    }
+
+    /// Clears `p`, shrinking the bounds if it was on their edge.
+    pub fn remove(&mut self, p: Point) -> Option<T> {
+        let value = self.cells.remove(&p)?;
+        if let Some((min, max)) = self.bounds {
+            if p.x == min.x || p.x == max.x || p.y == min.y || p.y == max.y {
+                self.bounds = self.compute_bounds();
+            }
+        }
+        Some(value)
+    }
+
+    fn compute_bounds(&self) -> Option<(Point, Point)> {
+        let mut points = self.cells.keys();
+        let first = *points.next()?;
+        Some(points.fold((first, first), |(min, max), p| {
+            (
+                Point::new(min.x.min(p.x), min.y.min(p.y)),
+                Point::new(max.x.max(p.x), max.y.max(p.y)),
+            )
+        }))
+    }
+
+    /// Inclusive corners of the smallest rectangle holding every occupied cell.
+    pub fn bounds(&self) -> Option<(Point, Point)> {
+        self.bounds
+    }
+
+    /// Occupied cells in no particular order.
+    pub fn iter(&self) -> impl Iterator<Item = (Point, &T)> {
+        self.cells.iter().map(|(p, cell)| (*p, cell))
+    }
+
+    /// Occupied cells one step in `dirs` from `p`, in the order of `dirs`.
+    pub fn neighbours<'a>(
+        &'a self,
+        p: Point,
+        dirs: &'a [Dir],
+    ) -> impl Iterator<Item = (Point, &'a T)> + 'a {
+        dirs.iter().filter_map(move |dir| {
+            let next = p + dir.offset();
+            self.get(next).map(|cell| (next, cell))
+        })
+    }
+
+    /// Copies the occupied bounding box into a dense grid, filling gaps with `empty`. Returns the
+    /// point that the grid's `(0, 0)` corresponds to.
+    pub fn to_grid(&self, empty: T) -> Option<(Point, Grid<T>)>
+    where
+        T: Clone,
+    {
+        let (min, max) = self.bounds?;
+        let width = (max.x - min.x + 1) as usize;
+        let height = (max.y - min.y + 1) as usize;
+        let mut grid = Grid::new(width, height, empty);
+        for (p, cell) in self.iter() {
+            let (x, y) = (p - min).to_index().expect("points lie within bounds");
+            grid[(x, y)] = cell.clone();
+        }
+        Some((min, grid))
+    }
+
+    /// Draws the occupied bounding box one row per line, using `cell` for every position.
+    pub fn render(&self, mut cell: impl FnMut(Option<&T>) -> char) -> String {
+        let Some((min, max)) = self.bounds else {
+            return String::new();
+        };
+        let mut out = String::new();
+        for y in min.y..=max.y {
+            for x in min.x..=max.x {
+                out.push(cell(self.get(Point::new(x, y))));
+            }
+            out.push('\n');
+        }
+        out
+    }
+}
+
+impl<T> FromIterator<(Point, T)> for SparseGrid<T> {
+    fn from_iter<I: IntoIterator<Item = (Point, T)>>(iter: I) -> Self {
+        let mut grid = SparseGrid::new();
+        grid.extend(iter);
+        grid
+    }
+}
+
+impl<T> Extend<(Point, T)> for SparseGrid<T> {
+    fn extend<I: IntoIterator<Item = (Point, T)>>(&mut self, iter: I) {
+        for (p, value) in iter {
+            self.insert(p, value);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bounds() {
+        let mut grid = SparseGrid::new();
+        assert_eq!(grid.bounds(), None);
+        grid.insert(Point::new(2, 3), 'a');
+        grid.insert(Point::new(-5, 1), 'b');
+        grid.insert(Point::new(0, -7), 'c');
+        assert_eq!(grid.len(), 3);
+        assert_eq!(grid.bounds(), Some((Point::new(-5, -7), Point::new(2, 3))));
+
+        assert_eq!(grid.remove(Point::new(-5, 1)), Some('b'));
+        assert_eq!(grid.remove(Point::new(-5, 1)), None);
+        assert_eq!(grid.bounds(), Some((Point::new(0, -7), Point::new(2, 3))));
+        grid.remove(Point::new(0, -7));
+        grid.remove(Point::new(2, 3));
+        assert!(grid.is_empty());
+        assert_eq!(grid.bounds(), None);
+    }
+
+    #[test]
+    fn test_neighbours() {
+        let grid: SparseGrid<char> = [(-1, -1), (0, -1), (1, 1), (5, 5)]
+            .into_iter()
+            .map(|(x, y)| (Point::new(x, y), '#'))
+            .collect();
+        let cardinal: Vec<Point> = grid
+            .neighbours(Point::ORIGIN, &Dir::CARDINAL)
+            .map(|(p, _)| p)
+            .collect();
+        assert_eq!(cardinal, [Point::new(0, -1)]);
+        assert_eq!(grid.neighbours(Point::ORIGIN, &Dir::ALL).count(), 3);
+    }
+
+    #[test]
+    fn test_dense_conversion() {
+        let dense = Grid::parse("..#\n#..\n...", |c| c).unwrap();
+        let mut sparse = SparseGrid::from_grid(&dense, |c| *c == '#');
+        assert_eq!(sparse.len(), 2);
+        assert_eq!(sparse.render(|c| *c.unwrap_or(&'.')), "..#\n#..\n");
+
+        sparse.insert(Point::new(-1, 2), '@');
+        assert_eq!(sparse.render(|c| *c.unwrap_or(&'.')), "...#\n.#..\n@...\n");
+
+        let (origin, grid) = sparse.to_grid('.').unwrap();
+        assert_eq!(origin, Point::new(-1, 0));
+        assert_eq!((grid.width(), grid.height()), (4, 3));
+        assert_eq!(grid[(0, 2)], '@');
+        assert_eq!(grid[(3, 0)], '#');
+        assert!(SparseGrid::<char>::new().to_grid('.').is_none());
+    }
+}
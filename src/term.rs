@@ -0,0 +1,68 @@
+use std::io::IsTerminal;
+
+/// ANSI foreground styles used when drawing to the terminal.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Style {
+    Plain,
+    Dim,
+    Red,
+    Green,
+    Yellow,
+    Magenta,
+    BoldMagenta,
+}
+
+impl Style {
+    fn code(self) -> Option<&'static str> {
+        match self {
+            Style::Plain => None,
+            Style::Dim => Some("2"),
+            Style::Red => Some("31"),
+            Style::Green => Some("32"),
+            Style::Yellow => Some("33"),
+            Style::Magenta => Some("35"),
+            Style::BoldMagenta => Some("1;35"),
+        }
+    }
+
+    /// Wraps `text` in this style's escape codes, or returns it unchanged when `color` is off.
+    pub fn paint(self, text: &str, color: bool) -> String {
+        match self.code() {
+            Some(code) if color => format!("\x1b[{code}m{text}\x1b[0m"),
+            _ => text.to_string(),
+        }
+    }
+}
+
+/// When to emit colour codes.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ColorMode {
+    /// Only when stdout is a terminal.
+    #[default]
+    Auto,
+    Always,
+    Never,
+}
+
+impl ColorMode {
+    pub fn enabled(self) -> bool {
+        match self {
+            ColorMode::Auto => std::io::stdout().is_terminal(),
+            ColorMode::Always => true,
+            ColorMode::Never => false,
+        }
+    }
+}
+
+impl std::str::FromStr for ColorMode {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s {
+            "auto" => ColorMode::Auto,
+            "always" => ColorMode::Always,
+            "never" => ColorMode::Never,
+            _ => anyhow::bail!("Invalid colour mode `{s}`: expected auto, always or never"),
+        })
+    }
+}
@@ -0,0 +1,220 @@
+use std::collections::VecDeque;
+
+use anyhow::bail;
+
+/// Finds every occurrence of a fixed set of byte patterns in one pass (Aho–Corasick), each
+/// pattern carrying a value of type `V`.
+#[derive(Debug, Clone)]
+pub struct Matcher<V> {
+    /// Full transition table: `transitions[state][byte]` is the next state.
+    transitions: Vec<[usize; 256]>,
+    /// Patterns ending at each state, longest first.
+    outputs: Vec<Vec<usize>>,
+    patterns: Vec<(usize, V)>,
+}
+
+/// A position in a [`Matcher`]'s automaton, for feeding it input a byte at a time.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct State(usize);
+
+impl State {
+    pub const START: State = State(0);
+}
+
+/// One occurrence of a pattern, covering `haystack[start..end]`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Match<'a, V> {
+    pub pattern: usize,
+    pub start: usize,
+    pub end: usize,
+    pub value: &'a V,
+}
+
+impl<V> Match<'_, V> {
+    pub fn len(&self) -> usize {
+        self.end - self.start
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.start == self.end
+    }
+}
+
+impl<V> Matcher<V> {
+    /// Builds the automaton. Patterns are numbered in the order given; empty patterns are
+    /// rejected since they would match everywhere.
+    pub fn new<P: AsRef<[u8]>>(patterns: impl IntoIterator<Item = (P, V)>) -> anyhow::Result<Self> {
+        let mut transitions = vec![[0; 256]];
+        let mut outputs = vec![Vec::new()];
+        let mut values = Vec::new();
+        for (index, (pattern, value)) in patterns.into_iter().enumerate() {
+            let pattern = pattern.as_ref();
+            if pattern.is_empty() {
+                bail!("Pattern {index} is empty");
+            }
+            let mut state = 0;
+            for byte in pattern {
+                let next = transitions[state][*byte as usize];
+                state = if next != 0 {
+                    next
+                } else {
+                    transitions.push([0; 256]);
+                    outputs.push(Vec::new());
+                    transitions[state][*byte as usize] = transitions.len() - 1;
+                    transitions.len() - 1
+                };
+            }
+            outputs[state].push(index);
+            values.push((pattern.len(), value));
+        }
+
+        // Breadth-first, so each state's failure link is finished before its children need it.
+        // Missing edges are filled in from the failure state, turning the trie into a DFA.
+        let mut fail = vec![0; transitions.len()];
+        let mut queue: VecDeque<usize> =
+            transitions[0].iter().copied().filter(|s| *s != 0).collect();
+        while let Some(state) = queue.pop_front() {
+            let inherited = outputs[fail[state]].clone();
+            outputs[state].extend(inherited);
+            let fallbacks = transitions[fail[state]];
+            for (next, fallback) in transitions[state].iter_mut().zip(fallbacks) {
+                if *next == 0 {
+                    *next = fallback;
+                } else {
+                    fail[*next] = fallback;
+                    queue.push_back(*next);
+                }
+            }
+        }
+
+        Ok(Matcher {
+            transitions,
+            outputs,
+            patterns: values,
+        })
+    }
+
+    pub fn value(&self, pattern: usize) -> &V {
+        &self.patterns[pattern].1
+    }
+
+    /// Advances the automaton by one byte.
+    pub fn step(&self, state: State, byte: u8) -> State {
+        State(self.transitions[state.0][byte as usize])
+    }
+
+    /// Patterns ending at the byte that led to `state`, longest first, as `(pattern, length,
+    /// value)`.
+    pub fn matches(&self, state: State) -> impl Iterator<Item = (usize, usize, &V)> {
+        self.outputs[state.0].iter().map(|p| {
+            let (len, value) = &self.patterns[*p];
+            (*p, *len, value)
+        })
+    }
+
+    /// Every match in `haystack`, overlapping ones included, ordered by end position and then
+    /// longest first.
+    pub fn find_iter<'a>(&'a self, haystack: &'a [u8]) -> FindIter<'a, V> {
+        FindIter {
+            matcher: self,
+            haystack,
+            position: 0,
+            state: State::START,
+            pending: &[],
+        }
+    }
+
+    /// The earliest-starting match, preferring the longest when several start together.
+    pub fn first<'a>(&'a self, haystack: &'a [u8]) -> Option<Match<'a, V>> {
+        self.find_iter(haystack)
+            .min_by_key(|m| (m.start, std::cmp::Reverse(m.end)))
+    }
+
+    /// The latest-starting match, preferring the longest when several start together.
+    pub fn last<'a>(&'a self, haystack: &'a [u8]) -> Option<Match<'a, V>> {
+        self.find_iter(haystack).max_by_key(|m| (m.start, m.end))
+    }
+}
+
+pub struct FindIter<'a, V> {
+    matcher: &'a Matcher<V>,
+    haystack: &'a [u8],
+    position: usize,
+    state: State,
+    /// Patterns ending at `position` that haven't been yielded yet.
+    pending: &'a [usize],
+}
+
+impl<'a, V> Iterator for FindIter<'a, V> {
+    type Item = Match<'a, V>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some((pattern, rest)) = self.pending.split_first() {
+                self.pending = rest;
+                let (len, value) = &self.matcher.patterns[*pattern];
+                return Some(Match {
+                    pattern: *pattern,
+                    start: self.position - len,
+                    end: self.position,
+                    value,
+                });
+            }
+            let byte = *self.haystack.get(self.position)?;
+            self.state = self.matcher.step(self.state, byte);
+            self.position += 1;
+            self.pending = &self.matcher.outputs[self.state.0];
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn spans(matcher: &Matcher<u32>, haystack: &str) -> Vec<(usize, usize, u32)> {
+        matcher
+            .find_iter(haystack.as_bytes())
+            .map(|m| (m.start, m.end, *m.value))
+            .collect()
+    }
+
+    #[test]
+    fn test_overlapping() {
+        let matcher = Matcher::new([("he", 1), ("she", 2), ("his", 3), ("hers", 4)]).unwrap();
+        assert_eq!(spans(&matcher, "ushers"), [(1, 4, 2), (2, 4, 1), (2, 6, 4)]);
+        assert_eq!(spans(&matcher, "ahishe"), [(1, 4, 3), (3, 6, 2), (4, 6, 1)]);
+        assert!(spans(&matcher, "xyz").is_empty());
+
+        let words = Matcher::new([("one", 1), ("eight", 8), ("two", 2)]).unwrap();
+        assert_eq!(
+            spans(&words, "oneightwo"),
+            [(0, 3, 1), (2, 7, 8), (6, 9, 2)]
+        );
+    }
+
+    #[test]
+    fn test_first_last() {
+        let matcher = Matcher::new([("nine", 9), ("nineteen", 19), ("teen", 10)]).unwrap();
+        let first = matcher.first(b"xnineteen").unwrap();
+        assert_eq!((first.start, first.len(), *first.value), (1, 8, 19));
+        let last = matcher.last(b"xnineteen").unwrap();
+        assert_eq!((last.start, *last.value), (5, 10));
+        assert_eq!(*matcher.value(1), 19);
+        assert!(matcher.first(b"").is_none());
+        assert!(Matcher::new([("", 0)]).is_err());
+    }
+
+    #[test]
+    fn test_step() {
+        let matcher = Matcher::new([("he", 1), ("she", 2)]).unwrap();
+        let state = b"sh".iter().fold(State::START, |s, b| matcher.step(s, *b));
+        assert_eq!(matcher.matches(state).count(), 0);
+        let state = matcher.step(state, b'e');
+        let found: Vec<(usize, usize, u32)> = matcher
+            .matches(state)
+            .map(|(p, len, v)| (p, len, *v))
+            .collect();
+        assert_eq!(found, [(1, 3, 2), (0, 2, 1)]);
+    }
+}
@@ -0,0 +1,66 @@
+use std::{path::PathBuf, str::FromStr};
+
+use anyhow::Context;
+
+mod matcher;
+
+pub use matcher::*;
+
+pub fn input_path(day: usize, problem: usize) -> PathBuf {
+    PathBuf::from(format!("inputs/day{:02}/problem{:02}.txt", day, problem))
+}
+
+pub fn example_path(day: usize, problem: usize) -> PathBuf {
+    PathBuf::from(format!("inputs/day{:02}/example{:02}.txt", day, problem))
+}
+
+pub fn read_input(day: usize, problem: usize) -> anyhow::Result<String> {
+    let path = input_path(day, problem);
+    std::fs::read_to_string(&path).with_context(|| format!("Failed to read {}", path.display()))
+}
+
+pub fn read_example(day: usize, problem: usize) -> anyhow::Result<String> {
+    let path = example_path(day, problem);
+    std::fs::read_to_string(&path).with_context(|| format!("Failed to read {}", path.display()))
+}
+
+/// Parses every line of `input`, tagging failures with the 1-based line number.
+pub fn parse_lines<T>(input: &str) -> anyhow::Result<Vec<T>>
+where
+    T: FromStr<Err = anyhow::Error>,
+{
+    input
+        .lines()
+        .enumerate()
+        .map(|(i, line)| T::from_str(line).with_context(|| format!("line {}: {line:?}", i + 1)))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_lines() {
+        let numbers: Vec<u64> = parse_lines::<Num>("1\n2\n3")
+            .unwrap()
+            .into_iter()
+            .map(|n| n.0)
+            .collect();
+        assert_eq!(numbers, [1, 2, 3]);
+
+        let err = parse_lines::<Num>("1\nx\n3").unwrap_err();
+        assert_eq!(format!("{err}"), "line 2: \"x\"");
+    }
+
+    #[derive(Debug)]
+    struct Num(u64);
+
+    impl FromStr for Num {
+        type Err = anyhow::Error;
+
+        fn from_str(s: &str) -> Result<Self, Self::Err> {
+            Ok(Num(s.parse()?))
+        }
+    }
+}